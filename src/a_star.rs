@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crate::{destination::Destination, geometry::Coordinates, graph::Graph, min_heap::MinHeap};
+
+pub trait Heuristic {
+    // Lower bound on the distance from `station` to `target`, both given as
+    // station IDs. Overestimating makes A* return longer paths.
+    fn estimate(&self, station: usize, target: usize) -> i32;
+}
+
+// No guidance at all, which makes A* a Dijkstra that stops at the target.
+pub struct ZeroHeuristic;
+
+impl Heuristic for ZeroHeuristic {
+    fn estimate(&self, _station: usize, _target: usize) -> i32 {
+        0
+    }
+}
+
+// Straight-line distance between station coordinates. Only admissible when no
+// edge is shorter than the straight line between its stations, which the
+// graph keeps track of in `coordinates_admissible`.
+pub struct CoordinateHeuristic<'a> {
+    pub coordinates: &'a HashMap<usize, Coordinates>,
+}
+
+impl CoordinateHeuristic<'_> {
+    pub fn new(graph: &Graph) -> CoordinateHeuristic<'_> {
+        CoordinateHeuristic {
+            coordinates: &graph.coordinates,
+        }
+    }
+}
+
+impl Heuristic for CoordinateHeuristic<'_> {
+    fn estimate(&self, station: usize, target: usize) -> i32 {
+        match (
+            self.coordinates.get(&station),
+            self.coordinates.get(&target),
+        ) {
+            (Some(station_coordinates), Some(target_coordinates)) => station_coordinates
+                .distance_to(target_coordinates)
+                .map_or(0, |distance| distance.floor() as i32),
+            _ => 0,
+        }
+    }
+}
+
+// ALT bounds: by the triangle inequality, the distance between two stations
+// is at least the difference of their distances to any landmark.
+pub struct LandmarkHeuristic {
+    pub distances: Vec<Vec<i32>>,
+}
+
+impl LandmarkHeuristic {
+    pub fn new(graph: &mut Graph, landmarks: Vec<String>) -> LandmarkHeuristic {
+        let mut distances: Vec<Vec<i32>> = vec![];
+        for landmark in landmarks {
+            match graph.get_station_id(&landmark) {
+                Some(id) => distances.push(graph.shortest_paths(id).distances.clone()),
+                None => panic!("Station not found"),
+            }
+        }
+        LandmarkHeuristic { distances }
+    }
+}
+
+impl Heuristic for LandmarkHeuristic {
+    fn estimate(&self, station: usize, target: usize) -> i32 {
+        let mut estimate = 0;
+        for distances in self.distances.iter() {
+            let (station_distance, target_distance) =
+                match (distances.get(station), distances.get(target)) {
+                    (Some(station_distance), Some(target_distance)) => {
+                        (*station_distance, *target_distance)
+                    }
+                    _ => continue,
+                };
+            if station_distance == i32::MAX || target_distance == i32::MAX {
+                continue;
+            }
+            estimate = estimate.max((station_distance - target_distance).abs());
+        }
+        estimate
+    }
+}
+
+impl Graph {
+    // Point-to-point shortest path which stops once nothing left on the
+    // frontier can reach `to` as soon, so paths of the same length still get
+    // their tie break. The adjacency has to be built.
+    pub fn a_star(
+        &self,
+        from: String,
+        to: String,
+        heuristic: &dyn Heuristic,
+    ) -> Option<Destination> {
+        let from_id = self.get_station_id(&from)?;
+        let to_id = self.get_station_id(&to)?;
+
+        let mut distances = vec![i32::MAX; self.stations.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.stations.len()];
+        let mut min_heap: MinHeap<usize, i32> = MinHeap::new();
+
+        distances[from_id] = 0;
+        min_heap.push(from_id, heuristic.estimate(from_id, to_id));
+
+        while let Some((current, estimate)) = min_heap.pop() {
+            if estimate > distances[to_id] {
+                break;
+            }
+            if current == to_id {
+                continue;
+            }
+            for (next, distance) in self.get_neighbours(current) {
+                let new_distance = distances[current] + distance;
+                if new_distance < distances[next]
+                    || (new_distance == distances[next]
+                        && self.is_better_tie(next, current, &|station| previous[station]))
+                {
+                    distances[next] = new_distance;
+                    previous[next] = Some(current);
+                    min_heap.push(next, new_distance + heuristic.estimate(next, to_id));
+                }
+            }
+        }
+
+        if distances[to_id] == i32::MAX {
+            return None;
+        }
+
+        let mut legs: Vec<(usize, i32, i32)> = vec![];
+        let mut station = to_id;
+        while let Some(prev) = previous[station] {
+            legs.push((station, distances[station] - distances[prev], 0));
+            station = prev;
+        }
+        legs.reverse();
+        Some(self.build_destination(from_id, to_id, legs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Coordinates, graph::Graph};
+
+    use super::{CoordinateHeuristic, LandmarkHeuristic, ZeroHeuristic};
+
+    fn create_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 40);
+        graph.add_edge("A".to_string(), "C".to_string(), 10);
+        graph.add_edge("B".to_string(), "C".to_string(), 20);
+        graph.add_edge("B".to_string(), "D".to_string(), 10);
+        graph.add_edge("C".to_string(), "D".to_string(), 50);
+        graph.add_edge("D".to_string(), "E".to_string(), 5);
+        graph.set_coordinates("A".to_string(), Coordinates::Planar { x: 0.0, y: 0.0 });
+        graph.set_coordinates("B".to_string(), Coordinates::Planar { x: 30.0, y: 0.0 });
+        graph.set_coordinates("C".to_string(), Coordinates::Planar { x: 10.0, y: 0.0 });
+        graph.set_coordinates("D".to_string(), Coordinates::Planar { x: 40.0, y: 0.0 });
+        graph.set_coordinates("E".to_string(), Coordinates::Planar { x: 45.0, y: 0.0 });
+        graph
+    }
+
+    #[test]
+    fn test_a_star_matches_dijkstra() {
+        let mut graph = create_graph();
+        let expected = graph.get_destination("A".to_string(), "E".to_string());
+        assert!(graph.coordinates_admissible);
+
+        let zero = graph.a_star("A".to_string(), "E".to_string(), &ZeroHeuristic);
+        assert_eq!(zero, Some(expected.clone()));
+
+        let coordinates = CoordinateHeuristic::new(&graph);
+        let with_coordinates = graph.a_star("A".to_string(), "E".to_string(), &coordinates);
+        assert_eq!(with_coordinates, Some(expected.clone()));
+
+        let landmarks = LandmarkHeuristic::new(&mut graph, vec!["E".to_string()]);
+        let with_landmarks = graph.a_star("A".to_string(), "E".to_string(), &landmarks);
+        assert_eq!(with_landmarks, Some(expected));
+    }
+
+    #[test]
+    fn test_inadmissible_coordinates() {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "C".to_string(), 15);
+        graph.add_edge("C".to_string(), "D".to_string(), 15);
+        graph.add_edge("A".to_string(), "B".to_string(), 10);
+        graph.add_edge("B".to_string(), "D".to_string(), 10);
+        graph.set_coordinates("A".to_string(), Coordinates::Planar { x: 0.0, y: 0.0 });
+        graph.set_coordinates("C".to_string(), Coordinates::Planar { x: 10.0, y: 0.0 });
+        graph.set_coordinates("D".to_string(), Coordinates::Planar { x: 20.0, y: 0.0 });
+        assert!(graph.coordinates_admissible);
+        // B is drawn far from everything but only 10 away from A and D.
+        graph.set_coordinates("B".to_string(), Coordinates::Planar { x: 0.0, y: 100.0 });
+        assert!(!graph.coordinates_admissible);
+
+        graph.build_adjacency();
+        let coordinates = CoordinateHeuristic::new(&graph);
+        let misled = graph.a_star("A".to_string(), "D".to_string(), &coordinates);
+        assert_eq!(
+            misled.map(|destination| destination.cumulative_distance),
+            Some(30)
+        );
+        let destination = graph.get_destination("A".to_string(), "D".to_string());
+        assert_eq!(destination.cumulative_distance, 20);
+
+        let mut graph = Graph::new();
+        graph.set_coordinates("A".to_string(), Coordinates::Planar { x: 0.0, y: 0.0 });
+        graph.set_coordinates("B".to_string(), Coordinates::Planar { x: 0.0, y: 100.0 });
+        graph.add_edge("A".to_string(), "B".to_string(), 100);
+        assert!(graph.coordinates_admissible);
+        graph.add_edge("A".to_string(), "B".to_string(), 10);
+        assert!(!graph.coordinates_admissible);
+    }
+
+    #[test]
+    fn test_a_star_unreachable() {
+        let mut graph = create_graph();
+        graph.add_edge("X".to_string(), "Y".to_string(), 5);
+        graph.build_adjacency();
+        assert_eq!(
+            graph.a_star("A".to_string(), "X".to_string(), &ZeroHeuristic),
+            None
+        );
+        assert_eq!(
+            graph.a_star("A".to_string(), "Z".to_string(), &ZeroHeuristic),
+            None
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::{graph::Graph, navigation::Navigation};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkAnalysis {
+    pub components: Vec<Vec<String>>,
+    // Stations and edges whose loss would split a component.
+    pub articulation_points: Vec<String>,
+    pub bridges: Vec<(String, String)>,
+    // Packages each train can get to and deliver, by train name.
+    pub reachable_packages: Vec<(String, Vec<String>)>,
+}
+
+impl Graph {
+    // Component index of every station. Edges always run both ways, so these
+    // are also the strongly connected components.
+    pub fn get_component_ids(&self) -> Vec<usize> {
+        let mut parents: Vec<usize> = (0..self.stations.len()).collect();
+        fn find(parents: &mut [usize], station: usize) -> usize {
+            let mut root = station;
+            while parents[root] != root {
+                root = parents[root];
+            }
+            let mut station = station;
+            while parents[station] != root {
+                let next = parents[station];
+                parents[station] = root;
+                station = next;
+            }
+            root
+        }
+        for (from, to, _, _) in self.edges.iter() {
+            let (from_root, to_root) = (find(&mut parents, *from), find(&mut parents, *to));
+            if from_root != to_root {
+                parents[from_root.max(to_root)] = from_root.min(to_root);
+            }
+        }
+
+        let mut roots: HashMap<usize, usize> = HashMap::new();
+        (0..self.stations.len())
+            .map(|station| {
+                let root = find(&mut parents, station);
+                let next_id = roots.len();
+                *roots.entry(root).or_insert(next_id)
+            })
+            .collect()
+    }
+    pub fn is_connected(&self, from: &str, to: &str) -> bool {
+        match (self.get_station_id(from), self.get_station_id(to)) {
+            (Some(from), Some(to)) => {
+                let component_ids = self.get_component_ids();
+                component_ids[from] == component_ids[to]
+            }
+            _ => false,
+        }
+    }
+    pub fn get_components(&self) -> Vec<Vec<String>> {
+        let component_ids = self.get_component_ids();
+        let count = component_ids.iter().map(|id| id + 1).max().unwrap_or(0);
+        let mut components: Vec<Vec<String>> = vec![vec![]; count];
+        for (station, id) in component_ids.into_iter().enumerate() {
+            if !self.is_removed(station) {
+                components[id].push(self.stations[station].clone());
+            }
+        }
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        components.retain(|component| !component.is_empty());
+        components.sort();
+        components
+    }
+    // Articulation points and bridges from a single iterative lowlink DFS,
+    // so deep networks do not overflow the stack.
+    pub fn get_cut_points(&mut self) -> (Vec<String>, Vec<(String, String)>) {
+        self.build_adjacency();
+        let count = self.stations.len();
+        let mut discovered: Vec<usize> = vec![usize::MAX; count];
+        let mut low: Vec<usize> = vec![0; count];
+        let mut is_articulation_point: Vec<bool> = vec![false; count];
+        let mut bridges: Vec<(String, String)> = vec![];
+        let mut time = 0;
+
+        for root in 0..count {
+            if discovered[root] != usize::MAX {
+                continue;
+            }
+            discovered[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+            // (station, parent, whether the arc back to the parent was skipped, next arc)
+            let mut stack: Vec<(usize, Option<usize>, bool, usize)> =
+                vec![(root, None, false, self.offsets[root])];
+
+            while let Some(top) = stack.last_mut() {
+                let (station, parent, skipped_parent, arc) = *top;
+                if arc < self.offsets[station + 1] {
+                    top.3 += 1;
+                    let next = self.targets[arc];
+                    if Some(next) == parent && !skipped_parent {
+                        top.2 = true;
+                        continue;
+                    }
+                    if discovered[next] == usize::MAX {
+                        discovered[next] = time;
+                        low[next] = time;
+                        time += 1;
+                        if station == root {
+                            root_children += 1;
+                        }
+                        stack.push((next, Some(station), false, self.offsets[next]));
+                    } else {
+                        low[station] = low[station].min(discovered[next]);
+                    }
+                } else {
+                    stack.pop();
+                    if let Some(parent) = parent {
+                        low[parent] = low[parent].min(low[station]);
+                        if low[station] > discovered[parent] {
+                            let mut bridge = (
+                                self.stations[parent].clone(),
+                                self.stations[station].clone(),
+                            );
+                            if bridge.0 > bridge.1 {
+                                bridge = (bridge.1, bridge.0);
+                            }
+                            bridges.push(bridge);
+                        }
+                        if parent != root && low[station] >= discovered[parent] {
+                            is_articulation_point[parent] = true;
+                        }
+                    }
+                }
+            }
+            if root_children > 1 {
+                is_articulation_point[root] = true;
+            }
+        }
+
+        let mut articulation_points: Vec<String> = vec![];
+        for (station, is_point) in is_articulation_point.into_iter().enumerate() {
+            if is_point {
+                articulation_points.push(self.stations[station].clone());
+            }
+        }
+        articulation_points.sort();
+        bridges.sort();
+        (articulation_points, bridges)
+    }
+}
+
+impl Navigation {
+    // Packages whose origin and destination are in the same component as the
+    // train, ignoring capacity.
+    pub fn get_reachable_packages(&self) -> Vec<(String, Vec<String>)> {
+        let component_ids = self.graph.get_component_ids();
+        let get_component_id = |station: &String| {
+            self.graph
+                .get_station_id(station)
+                .map(|station| component_ids[station])
+        };
+        let mut reachable_packages: Vec<(String, Vec<String>)> = vec![];
+        for (train_name, train) in self.trains.iter() {
+            let train_component_id = get_component_id(&train.current_location);
+            let mut packages: Vec<String> = vec![];
+            for (package_name, package) in self.packages.iter() {
+                if train_component_id.is_some()
+                    && get_component_id(&package.from) == train_component_id
+                    && get_component_id(&package.to) == train_component_id
+                {
+                    packages.push(package_name.clone());
+                }
+            }
+            packages.sort();
+            reachable_packages.push((train_name.clone(), packages));
+        }
+        reachable_packages.sort();
+        reachable_packages
+    }
+    pub fn analyse_network(&mut self) -> NetworkAnalysis {
+        let (articulation_points, bridges) = self.graph.get_cut_points();
+        NetworkAnalysis {
+            components: self.graph.get_components(),
+            articulation_points,
+            bridges,
+            reachable_packages: self.get_reachable_packages(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{input::Input, navigation::Navigation};
+
+    use super::NetworkAnalysis;
+
+    #[test]
+    fn test_analyse_network() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "C".to_string(), "A".to_string(), 10),
+                ("E4".to_string(), "C".to_string(), "D".to_string(), 10),
+                ("E5".to_string(), "D".to_string(), "E".to_string(), 10),
+                ("E6".to_string(), "X".to_string(), "Y".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "E".to_string()),
+                ("K2".to_string(), 5, "X".to_string(), "Y".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "B".to_string()),
+                ("Q2".to_string(), 10, "Y".to_string()),
+            ],
+            ..Default::default()
+        });
+        assert_eq!(
+            navigation.analyse_network(),
+            NetworkAnalysis {
+                components: vec![
+                    vec![
+                        "A".to_string(),
+                        "B".to_string(),
+                        "C".to_string(),
+                        "D".to_string(),
+                        "E".to_string(),
+                    ],
+                    vec!["X".to_string(), "Y".to_string()],
+                ],
+                articulation_points: vec!["C".to_string(), "D".to_string()],
+                bridges: vec![
+                    ("C".to_string(), "D".to_string()),
+                    ("D".to_string(), "E".to_string()),
+                    ("X".to_string(), "Y".to_string()),
+                ],
+                reachable_packages: vec![
+                    ("Q1".to_string(), vec!["K1".to_string()]),
+                    ("Q2".to_string(), vec!["K2".to_string()]),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use crate::{
+    destination::Destination, graph::Graph, json::Json, min_heap::MinHeap, movement::Movement,
+    navigation::Navigation, train::Train,
+};
+
+// What `calculate` minimises first. Whatever is left equal is settled by the
+// makespan, then by the number of trains. Under the cost objective trains
+// take the path that costs them the least, tolls included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Objective {
+    #[default]
+    Makespan,
+    Cost,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainCost {
+    pub train: String,
+    pub fixed_cost: i32,
+    pub distance: i32,
+    pub distance_cost: i32,
+    pub tolls: i32,
+    pub total: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanSummary {
+    pub makespan: i32,
+    pub number_of_trains: i32,
+    pub total_cost: i32,
+    // One entry per train used, by name.
+    pub trains: Vec<TrainCost>,
+}
+
+impl Graph {
+    // The path that costs a train the least in tolls and distance, the
+    // shortest of those when several cost the same. Closures are not looked
+    // at.
+    pub fn get_cheapest_destination(
+        &mut self,
+        from: String,
+        to: String,
+        tolls: &HashMap<String, i32>,
+        cost_per_distance: i32,
+    ) -> Option<Destination> {
+        let (from_id, to_id) = match (self.get_station_id(&from), self.get_station_id(&to)) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            _ => return None,
+        };
+        self.build_adjacency();
+        let edge_tolls: Vec<i32> = self
+            .edges
+            .iter()
+            .map(|(_, _, _, name)| tolls.get(name).copied().unwrap_or(0))
+            .collect();
+
+        // (cost, distance) to each station, and the station and adjacency
+        // index it was reached over.
+        let mut costs = vec![(i32::MAX, i32::MAX); self.stations.len()];
+        let mut previous: Vec<Option<(usize, usize)>> = vec![None; self.stations.len()];
+        let mut min_heap: MinHeap<usize, (i32, i32)> = MinHeap::new();
+
+        costs[from_id] = (0, 0);
+        min_heap.push(from_id, (0, 0));
+
+        while let Some((current, (cost, distance))) = min_heap.pop() {
+            if current == to_id {
+                break;
+            }
+            for i in self.offsets[current]..self.offsets[current + 1] {
+                let next = self.targets[i];
+                let new_cost = (
+                    cost + self.distances[i] * cost_per_distance + edge_tolls[self.edge_ids[i]],
+                    distance + self.distances[i],
+                );
+                if new_cost < costs[next] {
+                    costs[next] = new_cost;
+                    previous[next] = Some((current, i));
+                    min_heap.push(next, new_cost);
+                }
+            }
+        }
+
+        if costs[to_id].0 == i32::MAX {
+            return None;
+        }
+
+        let mut legs: Vec<(usize, i32, i32)> = vec![];
+        let mut edges: Vec<String> = vec![];
+        let mut station = to_id;
+        while let Some((prev, i)) = previous[station] {
+            legs.push((station, self.distances[i], 0));
+            edges.push(self.edges[self.edge_ids[i]].3.clone());
+            station = prev;
+        }
+        legs.reverse();
+        edges.reverse();
+        let mut destination = self.build_destination(from_id, to_id, legs);
+        // Parallel edges can differ in toll, so the ones taken are set here.
+        for (checkpoint, edge) in destination.checkpoints.iter_mut().zip(edges.iter()) {
+            checkpoint.edge = edge.clone();
+        }
+        if let Some(edge) = edges.last() {
+            destination.edge = edge.clone();
+        }
+        Some(destination)
+    }
+}
+
+impl Navigation {
+    // Where the train goes to get to `to`. Under the cost objective with
+    // tolls that is the cheapest path, unless a closure is in its way, and
+    // the earliest arrival otherwise.
+    pub fn get_train_destination(
+        &mut self,
+        train: &Train,
+        to: String,
+        departure_time: i32,
+    ) -> Destination {
+        if self.objective == Objective::Cost && !self.tolls.is_empty() {
+            self.stats.dijkstra_calls += 1;
+            if let Some(destination) = self.graph.get_cheapest_destination(
+                train.current_location.clone(),
+                to.clone(),
+                &self.tolls,
+                train.cost_per_distance,
+            ) {
+                if self.graph.is_path_open(&destination, departure_time) {
+                    return destination;
+                }
+            }
+        }
+        self.get_destination_at(train.current_location.clone(), to, departure_time)
+    }
+    // The toll of the edge the movement went over, 0 for breaks and edges
+    // without one.
+    pub fn get_toll(&self, movement: &Movement) -> i32 {
+        self.tolls.get(&movement.edge).copied().unwrap_or(0)
+    }
+    pub fn get_train_costs(&self, movements: &[Movement]) -> Vec<TrainCost> {
+        let mut costs: HashMap<String, TrainCost> = HashMap::new();
+        for movement in movements {
+            let train = match self.trains.get(&movement.train) {
+                Some(train) => train,
+                None => panic!("Train not found"),
+            };
+            let cost = costs.entry(train.name.clone()).or_insert(TrainCost {
+                train: train.name.clone(),
+                fixed_cost: train.fixed_cost,
+                distance: 0,
+                distance_cost: 0,
+                tolls: 0,
+                total: 0,
+            });
+            if movement.from != movement.to {
+                cost.distance += movement.end_time - movement.start_time;
+                cost.tolls += self.get_toll(movement);
+            }
+            cost.distance_cost = cost.distance * train.cost_per_distance;
+            cost.total = cost.fixed_cost + cost.distance_cost + cost.tolls;
+        }
+        let mut costs: Vec<TrainCost> = costs.into_values().collect();
+        costs.sort_by(|cost_a, cost_b| cost_a.train.cmp(&cost_b.train));
+        costs
+    }
+    pub fn get_cost(&self, movements: &[Movement]) -> i32 {
+        self.get_train_costs(movements)
+            .iter()
+            .map(|cost| cost.total)
+            .sum()
+    }
+    pub fn summarise(&self, movements: Vec<Movement>) -> PlanSummary {
+        let trains = self.get_train_costs(&movements);
+        PlanSummary {
+            makespan: Navigation::get_longest_distance_in_movements(movements.clone()),
+            number_of_trains: Navigation::get_number_of_trains(movements),
+            total_cost: trains.iter().map(|cost| cost.total).sum(),
+            trains,
+        }
+    }
+}
+
+impl From<PlanSummary> for Json {
+    fn from(summary: PlanSummary) -> Json {
+        let trains: Vec<Json> = summary
+            .trains
+            .into_iter()
+            .map(|cost| {
+                Json::Object(vec![
+                    ("train".to_string(), Json::from(cost.train)),
+                    ("fixed_cost".to_string(), Json::from(cost.fixed_cost)),
+                    ("distance".to_string(), Json::from(cost.distance)),
+                    ("distance_cost".to_string(), Json::from(cost.distance_cost)),
+                    ("tolls".to_string(), Json::from(cost.tolls)),
+                    ("total".to_string(), Json::from(cost.total)),
+                ])
+            })
+            .collect();
+        Json::Object(vec![
+            ("makespan".to_string(), Json::from(summary.makespan)),
+            (
+                "number_of_trains".to_string(),
+                Json::from(summary.number_of_trains),
+            ),
+            ("total_cost".to_string(), Json::from(summary.total_cost)),
+            ("trains".to_string(), Json::Array(trains)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{input::Input, movement::Movement, navigation::Navigation};
+
+    use super::{Objective, PlanSummary, TrainCost};
+
+    fn create_input(objective: Objective, tolls: Vec<(String, i32)>) -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "B".to_string(), "C".to_string())],
+            trains: vec![
+                ("Q1".to_string(), 10, "A".to_string()),
+                ("Q2".to_string(), 10, "B".to_string()),
+            ],
+            train_costs: vec![("Q1".to_string(), 10, 1), ("Q2".to_string(), 100, 5)],
+            tolls,
+            objective,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cost_objective() {
+        let mut navigation = Navigation::new(create_input(Objective::Makespan, vec![]));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            navigation.summarise(movements),
+            PlanSummary {
+                makespan: 10,
+                number_of_trains: 1,
+                total_cost: 150,
+                trains: vec![TrainCost {
+                    train: "Q2".to_string(),
+                    fixed_cost: 100,
+                    distance: 10,
+                    distance_cost: 50,
+                    tolls: 0,
+                    total: 150,
+                }],
+            }
+        );
+
+        let mut navigation = Navigation::new(create_input(Objective::Cost, vec![]));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let summary = navigation.summarise(movements);
+        assert_eq!((summary.makespan, summary.total_cost), (20, 30));
+        assert_eq!(
+            (navigation.stats.best_cost, navigation.stats.best_makespan),
+            (Some(30), Some(20))
+        );
+        assert_eq!(summary.trains[0].train, "Q1");
+
+        let mut navigation =
+            Navigation::new(create_input(Objective::Cost, vec![("E1".to_string(), 200)]));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let summary = navigation.summarise(movements.clone());
+        assert_eq!((summary.makespan, summary.total_cost), (10, 150));
+        let toll_movement = Movement {
+            from: "A".to_string(),
+            to: "B".to_string(),
+            edge: "E1".to_string(),
+            ..movements[0].clone()
+        };
+        assert_eq!(navigation.get_toll(&toll_movement), 200);
+    }
+
+    // The toll on E1 costs more than going around through C.
+    fn create_detour_input(objective: Objective) -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "A".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "C".to_string(), "B".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "B".to_string())],
+            trains: vec![("Q1".to_string(), 10, "A".to_string())],
+            train_costs: vec![("Q1".to_string(), 0, 1)],
+            tolls: vec![("E1".to_string(), 100)],
+            objective,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tolls_change_routes() {
+        let mut navigation = Navigation::new(create_detour_input(Objective::Makespan));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let edges: Vec<String> = movements
+            .iter()
+            .map(|movement| movement.edge.clone())
+            .collect();
+        assert_eq!(edges, vec!["E1".to_string()]);
+        assert_eq!(navigation.summarise(movements).total_cost, 110);
+
+        let mut navigation = Navigation::new(create_detour_input(Objective::Cost));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let edges: Vec<String> = movements
+            .iter()
+            .map(|movement| movement.edge.clone())
+            .collect();
+        assert_eq!(edges, vec!["E2".to_string(), "E3".to_string()]);
+        let summary = navigation.summarise(movements);
+        assert_eq!((summary.makespan, summary.total_cost), (20, 20));
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    panic,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{
+    movement::Movement,
+    navigation::Navigation,
+    package::Package,
+    stats::{ProgressCallback, SolverStats},
+    trace::TraceEntry,
+    train::Train,
+};
+
+// Trains and packages that can be planned without looking at the rest of the
+// problem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubProblem {
+    pub trains: Vec<String>,
+    pub packages: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecomposedPlan {
+    pub sub_problems: Vec<SubProblem>,
+    pub movements: Vec<Movement>,
+}
+
+impl Navigation {
+    // Splits the problem into groups where no train can carry a package of
+    // another group, either because it is in another part of the network,
+    // because it is too small or lacks the attributes the package requires,
+    // or because it is not allowed to. Trains that cannot carry any package
+    // are left out, since they never move.
+    pub fn decompose(
+        &self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+    ) -> Vec<SubProblem> {
+        let mut train_names: Vec<String> = trains.keys().cloned().collect();
+        train_names.sort();
+        let mut package_names: Vec<String> = packages.keys().cloned().collect();
+        package_names.sort();
+
+        // Trains first, then packages.
+        let mut parents: Vec<usize> = (0..train_names.len() + package_names.len()).collect();
+        fn find(parents: &mut [usize], node: usize) -> usize {
+            let mut root = node;
+            while parents[root] != root {
+                root = parents[root];
+            }
+            let mut node = node;
+            while parents[node] != root {
+                let next = parents[node];
+                parents[node] = root;
+                node = next;
+            }
+            root
+        }
+
+        let component_ids = self.graph.get_component_ids();
+        let get_component_id = |station: &String| {
+            self.graph
+                .get_station_id(station)
+                .map(|station| component_ids[station])
+        };
+        for (i, package_name) in package_names.iter().enumerate() {
+            let package = match packages.get(package_name) {
+                Some(package) => package,
+                None => panic!("Package not found"),
+            };
+            let mut assigned_train = package.delivered_by.clone();
+            if assigned_train.is_empty() {
+                assigned_train = package.picked_up_by.clone();
+            }
+            if assigned_train.is_empty() {
+                assigned_train = package.to_be_picked_up_by.clone();
+            }
+
+            for (j, train_name) in train_names.iter().enumerate() {
+                let train = match trains.get(train_name) {
+                    Some(train) => train,
+                    None => panic!("Train not found"),
+                };
+                let can_carry = if assigned_train.is_empty() {
+                    let package_component_id = get_component_id(&package.from);
+                    package_component_id.is_some()
+                        && get_component_id(&package.to) == package_component_id
+                        && get_component_id(&train.current_location) == package_component_id
+                        && package.fits_in(train)
+                        && package.is_allowed_on(train_name)
+                        && package.is_supported_by(train)
+                } else {
+                    assigned_train == *train_name
+                };
+                if can_carry {
+                    let (train_root, package_root) = (
+                        find(&mut parents, j),
+                        find(&mut parents, train_names.len() + i),
+                    );
+                    if train_root != package_root {
+                        parents[train_root.max(package_root)] = train_root.min(package_root);
+                    }
+                }
+            }
+        }
+
+        let mut groups: HashMap<usize, SubProblem> = HashMap::new();
+        for (j, train_name) in train_names.iter().enumerate() {
+            let root = find(&mut parents, j);
+            groups
+                .entry(root)
+                .or_insert(SubProblem {
+                    trains: vec![],
+                    packages: vec![],
+                })
+                .trains
+                .push(train_name.clone());
+        }
+        for (i, package_name) in package_names.iter().enumerate() {
+            let root = find(&mut parents, train_names.len() + i);
+            groups
+                .entry(root)
+                .or_insert(SubProblem {
+                    trains: vec![],
+                    packages: vec![],
+                })
+                .packages
+                .push(package_name.clone());
+        }
+
+        let mut sub_problems: Vec<SubProblem> = groups
+            .into_values()
+            .filter(|sub_problem| !sub_problem.packages.is_empty())
+            .collect();
+        sub_problems.sort_by(|sub_problem_a, sub_problem_b| {
+            sub_problem_a.packages.cmp(&sub_problem_b.packages)
+        });
+        sub_problems
+    }
+
+    // Solves every sub-problem on its own thread and merges the plans. The
+    // longest delivery time is the same as `calculate` would find, but the
+    // number of trains is only minimised within each sub-problem.
+    pub fn calculate_decomposed(
+        &mut self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> DecomposedPlan {
+        let sub_problems = self.decompose(trains.clone(), packages.clone());
+        self.calculate_sub_problems(sub_problems, trains, packages, movements)
+    }
+
+    // The stats and trace of every sub-problem are added to this
+    // navigation's. Progress is reported by each sub-problem as it goes, with
+    // its own stats, and once more with the merged ones.
+    fn calculate_sub_problems(
+        &mut self,
+        sub_problems: Vec<SubProblem>,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> DecomposedPlan {
+        let progress: Option<Arc<Mutex<ProgressCallback>>> = self
+            .progress
+            .take()
+            .map(|progress| Arc::new(Mutex::new(progress)));
+
+        let mut sub_navigations: Vec<Navigation> = vec![];
+        for sub_problem in sub_problems.iter() {
+            let mut sub_trains: HashMap<String, Train> = HashMap::new();
+            for train_name in sub_problem.trains.iter() {
+                match trains.get(train_name) {
+                    Some(train) => sub_trains.insert(train_name.clone(), train.clone()),
+                    None => panic!("Train not found"),
+                };
+            }
+            let mut sub_packages: HashMap<String, Package> = HashMap::new();
+            for package_name in sub_problem.packages.iter() {
+                match packages.get(package_name) {
+                    Some(package) => sub_packages.insert(package_name.clone(), package.clone()),
+                    None => panic!("Package not found"),
+                };
+            }
+            let mut sub_navigation = Navigation::from_graph(
+                self.graph.clone(),
+                self.edges.clone(),
+                sub_trains,
+                sub_packages,
+            )
+            .with_options_of(self);
+            sub_navigation.trace = self.trace.as_ref().map(|_| vec![]);
+            sub_navigation.progress_interval = self.progress_interval;
+            if let Some(progress) = progress.clone() {
+                sub_navigation.progress = Some(Box::new(move |stats: &SolverStats| {
+                    let mut progress = progress.lock().unwrap();
+                    progress(stats)
+                }));
+            }
+            sub_navigations.push(sub_navigation);
+        }
+
+        let sub_results: Vec<(Vec<Movement>, SolverStats, Option<Vec<TraceEntry>>)> =
+            thread::scope(|scope| {
+                let handles: Vec<_> = sub_navigations
+                    .into_iter()
+                    .map(|mut navigation| {
+                        let sub_problem_movements: Vec<Movement> = movements
+                            .iter()
+                            .filter(|movement| navigation.trains.contains_key(&movement.train))
+                            .cloned()
+                            .collect();
+                        scope.spawn(move || {
+                            let movements = navigation.calculate(
+                                navigation.trains.clone(),
+                                navigation.packages.clone(),
+                                sub_problem_movements,
+                            );
+                            (movements, navigation.stats, navigation.trace)
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| match handle.join() {
+                        Ok(result) => result,
+                        Err(error) => panic::resume_unwind(error),
+                    })
+                    .collect()
+            });
+
+        let mut all_movements: Vec<Movement> = vec![];
+        for (sub_movements, stats, trace) in sub_results {
+            all_movements.extend(sub_movements);
+            self.stats.add_sub_problem(&stats);
+            if let (Some(all_trace), Some(trace)) = (self.trace.as_mut(), trace) {
+                all_trace.extend(trace);
+            }
+        }
+        // Every sub-navigation holding it is gone by now.
+        self.progress = progress.map(|progress| match Arc::try_unwrap(progress) {
+            Ok(progress) => progress.into_inner().unwrap(),
+            Err(_) => panic!("Progress still in use"),
+        });
+        if let Some(progress) = self.progress.as_mut() {
+            progress(&self.stats);
+        }
+
+        for movement in movements {
+            if !all_movements.contains(&movement) {
+                all_movements.push(movement);
+            }
+        }
+        all_movements.sort_by(|movement_a, movement_b| {
+            let train_cmp = movement_a.train.cmp(&movement_b.train);
+            let start_time_cmp = movement_a.start_time.cmp(&movement_b.start_time);
+            if train_cmp == Ordering::Equal {
+                return start_time_cmp;
+            }
+            train_cmp
+        });
+
+        DecomposedPlan {
+            sub_problems,
+            movements: all_movements,
+        }
+    }
+
+    // Plans a whole problem, in parallel when it splits into more than one
+    // sub-problem.
+    pub fn solve(
+        &mut self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> Vec<Movement> {
+        let sub_problems = self.decompose(trains.clone(), packages.clone());
+        if sub_problems.len() > 1 {
+            return self
+                .calculate_sub_problems(sub_problems, trains, packages, movements)
+                .movements;
+        }
+        self.calculate(trains, packages, movements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{input::Input, navigation::Navigation, stats::SolverStats};
+
+    use super::SubProblem;
+
+    #[test]
+    fn test_decompose() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "X".to_string(), "Y".to_string(), 20),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 50, "B".to_string(), "C".to_string()),
+                ("K3".to_string(), 5, "X".to_string(), "Y".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "A".to_string()),
+                ("Q2".to_string(), 60, "C".to_string()),
+                ("Q3".to_string(), 10, "Y".to_string()),
+                ("Q4".to_string(), 10, "Z".to_string()),
+            ],
+            ..Default::default()
+        });
+        navigation.trace = Some(vec![]);
+        let reports: Arc<Mutex<Vec<SolverStats>>> = Arc::new(Mutex::new(vec![]));
+        let progress_reports = reports.clone();
+        navigation.progress = Some(Box::new(move |stats: &SolverStats| {
+            progress_reports.lock().unwrap().push(stats.clone());
+        }));
+        let plan = navigation.calculate_decomposed(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            plan.sub_problems,
+            vec![
+                SubProblem {
+                    trains: vec!["Q1".to_string(), "Q2".to_string()],
+                    packages: vec!["K1".to_string(), "K2".to_string()],
+                },
+                SubProblem {
+                    trains: vec!["Q3".to_string()],
+                    packages: vec!["K3".to_string()],
+                },
+            ]
+        );
+        let legs: Vec<(String, String, String, i32)> = plan
+            .movements
+            .iter()
+            .map(|movement| {
+                (
+                    movement.train.clone(),
+                    movement.from.clone(),
+                    movement.to.clone(),
+                    movement.end_time,
+                )
+            })
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                ("Q1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("Q1".to_string(), "B".to_string(), "C".to_string(), 20),
+                ("Q2".to_string(), "C".to_string(), "B".to_string(), 10),
+                ("Q2".to_string(), "B".to_string(), "C".to_string(), 20),
+                ("Q3".to_string(), "Y".to_string(), "X".to_string(), 20),
+                ("Q3".to_string(), "X".to_string(), "Y".to_string(), 40),
+            ]
+        );
+
+        // The sub-problems' work shows up on the navigation that split them.
+        let stats = navigation.stats.clone();
+        assert!(stats.states_explored > 0);
+        assert!(stats.branches_explored > 0);
+        assert_eq!(stats.best_makespan, Some(40));
+        assert_eq!(stats.best_number_of_trains, Some(3));
+        let trace = navigation.trace.clone().unwrap();
+        assert!(trace.iter().any(|entry| entry.package == "K1"));
+        assert!(trace.iter().any(|entry| entry.package == "K3"));
+        assert_eq!(reports.lock().unwrap().last(), Some(&stats));
+        assert!(navigation.progress.is_some());
+        assert_eq!(
+            navigation.solve(
+                navigation.trains.clone(),
+                navigation.packages.clone(),
+                vec![],
+            ),
+            plan.movements
+        );
+    }
+}
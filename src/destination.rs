@@ -5,8 +5,19 @@ pub struct Destination {
     pub from: String,
     pub to: String,
     pub distance: i32,
+    // The edge of the last leg, the others are on the checkpoints.
+    pub edge: String,
     pub cumulative_distance: i32,
     pub checkpoints: Vec<Route>,
+    // Time spent waiting for closures to lift before each leg, empty when the
+    // path never has to wait.
+    pub waits: Vec<i32>,
+}
+
+impl Default for Destination {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Destination {
@@ -15,8 +26,10 @@ impl Destination {
             from: "".to_string(),
             to: "".to_string(),
             distance: 0,
+            edge: "".to_string(),
             cumulative_distance: 0,
             checkpoints: [].to_vec(),
+            waits: [].to_vec(),
         }
     }
 }
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use crate::{movement::Movement, navigation::Navigation, package::Package};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Disruption {
+    // The train finishes the movement it is on at `time` and then stops,
+    // leaving any packages on board at that station once it arrives.
+    TrainOutOfService { train: String, time: i32 },
+    // No train may be travelling on the edge between `from` and `to`.
+    EdgeClosed { edge: String, from: i32, to: i32 },
+}
+
+impl Disruption {
+    pub fn time(&self) -> i32 {
+        match self {
+            Disruption::TrainOutOfService { time, .. } => *time,
+            Disruption::EdgeClosed { from, .. } => *from,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryChange {
+    pub package: String,
+    pub old_train: String,
+    pub old_time: i32,
+    pub new_train: String,
+    pub new_time: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replan {
+    pub movements: Vec<Movement>,
+    pub changed_deliveries: Vec<DeliveryChange>,
+}
+
+impl Navigation {
+    pub fn get_deliveries(movements: Vec<Movement>) -> HashMap<String, (String, i32)> {
+        let mut deliveries: HashMap<String, (String, i32)> = HashMap::new();
+        for movement in movements {
+            for package_name in movement.packages_delivered {
+                deliveries.insert(package_name, (movement.train.clone(), movement.end_time));
+            }
+        }
+        deliveries
+    }
+
+    // Movements already started when the disruption happens are kept as they
+    // are, everything after that is recalculated from where the trains and
+    // packages are at that point.
+    pub fn replan(&self, movements: Vec<Movement>, disruption: Disruption) -> Replan {
+        let time = disruption.time();
+        let mut kept_movements: Vec<Movement> = vec![];
+        for movement in movements.clone() {
+            if movement.start_time < time {
+                kept_movements.push(movement);
+            }
+        }
+
+        let mut graph = self.graph.clone();
+        let mut failed_train = "".to_string();
+        match disruption.clone() {
+            Disruption::TrainOutOfService { train, .. } => {
+                if !self.trains.contains_key(&train) {
+                    panic!("Train not found");
+                }
+                failed_train = train;
+            }
+            Disruption::EdgeClosed { edge, from, to } => match self.edges.get(&edge) {
+                Some((edge_from, edge_to, _)) => {
+                    graph.add_closure(edge_from.clone(), edge_to.clone(), from, to)
+                }
+                None => panic!("Edge not found"),
+            },
+        }
+
+        let mut trains = self.trains.clone();
+        let mut packages = self.packages.clone();
+        for (_, train) in trains.iter_mut() {
+            train.available_at = train.available_at.max(time);
+        }
+
+        for movement in kept_movements.clone() {
+            let train = match trains.get_mut(&movement.train) {
+                Some(train) => train,
+                None => panic!("Train not found"),
+            };
+            train.current_location = movement.to.clone();
+            train.total_distance += movement.end_time - movement.start_time;
+            train.available_at = train.available_at.max(movement.end_time);
+
+            for package_name in movement.packages_picked_up {
+                train.packages_picked_up.push(package_name.clone());
+                let package = match packages.get_mut(&package_name) {
+                    Some(package) => package,
+                    None => panic!("Package not found"),
+                };
+                package.to_be_picked_up_by = train.name.clone();
+                package.picked_up_by = train.name.clone();
+            }
+            for package_name in movement.packages_delivered {
+                train
+                    .packages_picked_up
+                    .retain(|package_picked_up| package_picked_up != &package_name);
+                train.packages_delivered.push(package_name.clone());
+                let package = match packages.get_mut(&package_name) {
+                    Some(package) => package,
+                    None => panic!("Package not found"),
+                };
+                package.delivered_by = train.name.clone();
+            }
+            for package_name in movement.packages_transferred {
+                train
+                    .packages_picked_up
+                    .retain(|package_picked_up| package_picked_up != &package_name);
+                let package = match packages.get_mut(&package_name) {
+                    Some(package) => package,
+                    None => panic!("Package not found"),
+                };
+                package.to_be_picked_up_by = "".to_string();
+                package.picked_up_by = "".to_string();
+                package.location = movement.to.clone();
+                package.available_at = movement.end_time;
+                package.legs.push((train.name.clone(), movement.to.clone()));
+            }
+        }
+
+        if let Some(train) = trains.remove(&failed_train) {
+            for package_name in train.packages_picked_up {
+                let package = match packages.get(&package_name) {
+                    Some(package) => package.clone(),
+                    None => panic!("Package not found"),
+                };
+                // Keeps the package's requirements, but it starts over from
+                // where the train stopped.
+                let stranded_package = Package {
+                    from: train.current_location.clone(),
+                    location: train.current_location.clone(),
+                    available_at: train.available_at,
+                    legs: vec![],
+                    to_be_picked_up_by: "".to_string(),
+                    picked_up_by: "".to_string(),
+                    ..package
+                };
+                packages.insert(package_name, stranded_package);
+            }
+        }
+
+        let mut navigation =
+            Navigation::from_graph(graph, self.edges.clone(), trains.clone(), packages.clone())
+                .with_options_of(self);
+        let new_movements = navigation.calculate(trains, packages, kept_movements);
+
+        let old_deliveries = Navigation::get_deliveries(movements);
+        let new_deliveries = Navigation::get_deliveries(new_movements.clone());
+        let mut changed_deliveries: Vec<DeliveryChange> = vec![];
+        for (package_name, (new_train, new_time)) in new_deliveries {
+            let (old_train, old_time) = match old_deliveries.get(&package_name) {
+                Some(delivery) => delivery.clone(),
+                None => ("".to_string(), 0),
+            };
+            if old_train != new_train || old_time != new_time {
+                changed_deliveries.push(DeliveryChange {
+                    package: package_name,
+                    old_train,
+                    old_time,
+                    new_train,
+                    new_time,
+                });
+            }
+        }
+        changed_deliveries.sort_by(|change_a, change_b| change_a.package.cmp(&change_b.package));
+
+        Replan {
+            movements: new_movements,
+            changed_deliveries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{input::Input, movement::Movement, navigation::Navigation};
+
+    use super::{DeliveryChange, Disruption};
+
+    #[test]
+    fn test_replan_train_out_of_service() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![
+                ("Q1".to_string(), 6, "A".to_string()),
+                ("Q2".to_string(), 6, "C".to_string()),
+            ],
+            ..Default::default()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let replan = navigation.replan(
+            movements,
+            Disruption::TrainOutOfService {
+                train: "Q1".to_string(),
+                time: 15,
+            },
+        );
+        assert_eq!(
+            replan.movements,
+            vec![
+                Movement {
+                    start_time: 0,
+                    end_time: 30,
+                    from: "A".to_string(),
+                    to: "B".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 15,
+                    end_time: 25,
+                    from: "C".to_string(),
+                    to: "B".to_string(),
+                    edge: "E2".to_string(),
+                    train: "Q2".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 30,
+                    end_time: 40,
+                    from: "B".to_string(),
+                    to: "C".to_string(),
+                    edge: "E2".to_string(),
+                    train: "Q2".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            replan.changed_deliveries,
+            vec![DeliveryChange {
+                package: "K1".to_string(),
+                old_train: "Q1".to_string(),
+                old_time: 40,
+                new_train: "Q2".to_string(),
+                new_time: 40,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_replan_edge_closed() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "A".to_string(), "D".to_string(), 15),
+                ("E4".to_string(), "D".to_string(), "C".to_string(), 15),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            ..Default::default()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let replan = navigation.replan(
+            movements,
+            Disruption::EdgeClosed {
+                edge: "E2".to_string(),
+                from: 5,
+                to: 100,
+            },
+        );
+        assert_eq!(
+            replan.movements,
+            vec![
+                Movement {
+                    start_time: 0,
+                    end_time: 10,
+                    from: "A".to_string(),
+                    to: "B".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 10,
+                    end_time: 20,
+                    from: "B".to_string(),
+                    to: "A".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 20,
+                    end_time: 35,
+                    from: "A".to_string(),
+                    to: "D".to_string(),
+                    edge: "E3".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 35,
+                    end_time: 50,
+                    from: "D".to_string(),
+                    to: "C".to_string(),
+                    edge: "E4".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            replan.changed_deliveries,
+            vec![DeliveryChange {
+                package: "K1".to_string(),
+                old_train: "Q1".to_string(),
+                old_time: 20,
+                new_train: "Q1".to_string(),
+                new_time: 50,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_replan_edge_reopening() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "A".to_string(), "D".to_string(), 15),
+                ("E4".to_string(), "D".to_string(), "C".to_string(), 15),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            ..Default::default()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let replan = navigation.replan(
+            movements,
+            Disruption::EdgeClosed {
+                edge: "E2".to_string(),
+                from: 5,
+                to: 12,
+            },
+        );
+        assert_eq!(
+            replan.movements,
+            vec![
+                Movement {
+                    start_time: 0,
+                    end_time: 10,
+                    from: "A".to_string(),
+                    to: "B".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 12,
+                    end_time: 22,
+                    from: "B".to_string(),
+                    to: "C".to_string(),
+                    edge: "E2".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            replan.changed_deliveries,
+            vec![DeliveryChange {
+                package: "K1".to_string(),
+                old_train: "Q1".to_string(),
+                old_time: 20,
+                new_train: "Q1".to_string(),
+                new_time: 22,
+            }]
+        );
+    }
+}
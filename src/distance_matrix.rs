@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::{destination::Destination, graph::Graph, json::Json};
+
+// Shortest distances and paths between a fixed set of stations, `None` where
+// a pair is not connected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceMatrix {
+    pub stations: Vec<String>,
+    pub indices: HashMap<String, usize>,
+    pub distances: Vec<Vec<Option<i32>>>,
+    pub paths: Vec<Vec<Option<Destination>>>,
+}
+
+impl Default for DistanceMatrix {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl DistanceMatrix {
+    pub fn new(stations: Vec<String>) -> DistanceMatrix {
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for (i, station) in stations.iter().enumerate() {
+            indices.insert(station.clone(), i);
+        }
+        DistanceMatrix {
+            distances: vec![vec![None; stations.len()]; stations.len()],
+            paths: vec![vec![None; stations.len()]; stations.len()],
+            stations,
+            indices,
+        }
+    }
+    pub fn get_distance(&self, from: &str, to: &str) -> Option<i32> {
+        match (self.indices.get(from), self.indices.get(to)) {
+            (Some(from), Some(to)) => self.distances[*from][*to],
+            _ => None,
+        }
+    }
+    pub fn get_destination(&self, from: &str, to: &str) -> Option<&Destination> {
+        match (self.indices.get(from), self.indices.get(to)) {
+            (Some(from), Some(to)) => self.paths[*from][*to].as_ref(),
+            _ => None,
+        }
+    }
+    pub fn to_csv(&self) -> String {
+        let mut csv = "".to_string();
+        for station in self.stations.iter() {
+            csv = format!("{},{}", csv, station);
+        }
+        for (i, station) in self.stations.iter().enumerate() {
+            csv = format!("{}\n{}", csv, station);
+            for distance in self.distances[i].iter() {
+                match distance {
+                    Some(distance) => csv = format!("{},{}", csv, distance),
+                    None => csv = format!("{},", csv),
+                }
+            }
+        }
+        format!("{}\n", csv)
+    }
+    pub fn to_json(&self) -> String {
+        let mut paths: Vec<Json> = vec![];
+        for row in self.paths.iter() {
+            let mut path_row: Vec<Json> = vec![];
+            for path in row.iter() {
+                path_row.push(match path {
+                    Some(destination) => {
+                        let mut stations: Vec<String> = vec![destination.from.clone()];
+                        for checkpoint in destination.checkpoints.iter() {
+                            stations.push(checkpoint.to.clone());
+                        }
+                        if destination.to != destination.from {
+                            stations.push(destination.to.clone());
+                        }
+                        Json::from(stations)
+                    }
+                    None => Json::Null,
+                });
+            }
+            paths.push(Json::Array(path_row));
+        }
+        Json::Object(vec![
+            ("stations".to_string(), Json::from(self.stations.clone())),
+            ("distances".to_string(), Json::from(self.distances.clone())),
+            ("paths".to_string(), Json::Array(paths)),
+        ])
+        .to_string()
+    }
+}
+
+impl Graph {
+    // Runs one Dijkstra per station in `stations` and keeps only the entries
+    // between those stations. Unknown stations are kept as unreachable.
+    pub fn all_pairs(&mut self, stations: Vec<String>) -> DistanceMatrix {
+        let mut matrix = DistanceMatrix::new(stations.clone());
+        for (i, from) in stations.iter().enumerate() {
+            let from_id = match self.get_station_id(from) {
+                Some(id) => id,
+                None => continue,
+            };
+            for (j, to) in stations.iter().enumerate() {
+                let to_id = match self.get_station_id(to) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                if let Some(destination) = self.get_destination_by_id(from_id, to_id) {
+                    matrix.distances[i][j] = Some(destination.cumulative_distance);
+                    matrix.paths[i][j] = Some(destination);
+                }
+            }
+        }
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+
+    #[test]
+    fn test_all_pairs() {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 40);
+        graph.add_edge("A".to_string(), "C".to_string(), 10);
+        graph.add_edge("B".to_string(), "C".to_string(), 20);
+        graph.add_edge("B".to_string(), "D".to_string(), 10);
+        graph.add_edge("X".to_string(), "Y".to_string(), 5);
+        let matrix = graph.all_pairs(vec!["A".to_string(), "D".to_string(), "X".to_string()]);
+        assert_eq!(matrix.get_distance("A", "D"), Some(40));
+        assert_eq!(matrix.get_distance("D", "A"), Some(40));
+        assert_eq!(matrix.get_distance("A", "X"), None);
+        assert_eq!(
+            matrix
+                .get_destination("A", "D")
+                .map(|destination| destination.checkpoints.len()),
+            Some(2)
+        );
+        assert_eq!(matrix.to_csv(), ",A,D,X\nA,0,40,\nD,40,0,\nX,,,0\n");
+        assert_eq!(
+            matrix.to_json(),
+            "{\"stations\":[\"A\",\"D\",\"X\"],\
+             \"distances\":[[0,40,null],[40,0,null],[null,null,0]],\
+             \"paths\":[[[\"A\"],[\"A\",\"C\",\"B\",\"D\"],null],\
+             [[\"D\",\"B\",\"C\",\"A\"],[\"D\"],null],\
+             [null,null,[\"X\"]]]}"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use crate::{movement::Movement, navigation::Navigation, package::Package};
+
+// Why a package went on its train: the plan's makespan and number of trains,
+// and the best plan with the package on another train.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackageExplanation {
+    pub package: String,
+    pub train: String,
+    pub makespan: i32,
+    pub number_of_trains: i32,
+    // "" when no other train could carry the package.
+    pub alternative_train: String,
+    pub alternative_makespan: Option<i32>,
+    pub alternative_number_of_trains: Option<i32>,
+    // How much later the alternative finishes.
+    pub makespan_difference: Option<i32>,
+    // Other trains that could carry the package, but with which no plan
+    // delivers everything, for example because of a train's limits.
+    pub infeasible_trains: Vec<String>,
+}
+
+impl Navigation {
+    // Plan for the trains and packages with the package pinned to the train,
+    // solved from scratch since the cache does not know about pins. `None`
+    // when there is no plan with the package on that train.
+    pub fn calculate_pinned(&self, package_name: &str, train_name: &str) -> Option<Vec<Movement>> {
+        let mut packages = self.packages.clone();
+        match packages.get_mut(package_name) {
+            Some(package) => package.pinned_to = train_name.to_string(),
+            None => panic!("Package not found"),
+        }
+        let mut navigation = Navigation::from_graph(
+            self.graph.clone(),
+            self.edges.clone(),
+            self.trains.clone(),
+            packages.clone(),
+        )
+        .with_options_of(self);
+        navigation.try_calculate(self.trains.clone(), packages, vec![])
+    }
+
+    // Explains every package delivered in the plan by solving again with it
+    // pinned to each other train that can reach and carry it.
+    pub fn explain(&self, movements: Vec<Movement>) -> Vec<PackageExplanation> {
+        let makespan = Navigation::get_longest_distance_in_movements(movements.clone());
+        let number_of_trains = Navigation::get_number_of_trains(movements.clone());
+        let component_ids = self.graph.get_component_ids();
+        let get_component_id = |station: &String| {
+            self.graph
+                .get_station_id(station)
+                .map(|station| component_ids[station])
+        };
+
+        let mut deliveries: Vec<(String, (String, i32))> =
+            Navigation::get_deliveries(movements).into_iter().collect();
+        deliveries.sort();
+        let mut train_names: Vec<String> = self.trains.keys().cloned().collect();
+        train_names.sort();
+
+        let mut explanations: Vec<PackageExplanation> = vec![];
+        for (package_name, (train_name, _)) in deliveries {
+            let package: &Package = match self.packages.get(&package_name) {
+                Some(package) => package,
+                None => panic!("Package not found"),
+            };
+            let mut explanation = PackageExplanation {
+                package: package_name.clone(),
+                train: train_name.clone(),
+                makespan,
+                number_of_trains,
+                alternative_train: "".to_string(),
+                alternative_makespan: None,
+                alternative_number_of_trains: None,
+                makespan_difference: None,
+                infeasible_trains: vec![],
+            };
+
+            let mut alternatives: HashMap<String, (i32, i32)> = HashMap::new();
+            for other_train_name in train_names.iter() {
+                let other_train = &self.trains[other_train_name];
+                if *other_train_name == train_name
+                    || !package.fits_in(other_train)
+                    || !package.is_allowed_on(other_train_name)
+                    || !package.is_supported_by(other_train)
+                    || get_component_id(&other_train.current_location).is_none()
+                    || get_component_id(&other_train.current_location)
+                        != get_component_id(&package.from)
+                {
+                    continue;
+                }
+                let alternative_movements =
+                    match self.calculate_pinned(&package_name, other_train_name) {
+                        Some(alternative_movements) => alternative_movements,
+                        None => {
+                            explanation.infeasible_trains.push(other_train_name.clone());
+                            continue;
+                        }
+                    };
+                alternatives.insert(
+                    other_train_name.clone(),
+                    (
+                        Navigation::get_longest_distance_in_movements(
+                            alternative_movements.clone(),
+                        ),
+                        Navigation::get_number_of_trains(alternative_movements),
+                    ),
+                );
+            }
+
+            let mut alternatives: Vec<(String, (i32, i32))> = alternatives.into_iter().collect();
+            alternatives.sort_by(|(train_a, cost_a), (train_b, cost_b)| {
+                cost_a.cmp(cost_b).then(train_a.cmp(train_b))
+            });
+            if let Some((alternative_train, (alternative_makespan, alternative_number_of_trains))) =
+                alternatives.into_iter().next()
+            {
+                explanation.alternative_train = alternative_train;
+                explanation.alternative_makespan = Some(alternative_makespan);
+                explanation.alternative_number_of_trains = Some(alternative_number_of_trains);
+                explanation.makespan_difference = Some(alternative_makespan - makespan);
+            }
+            explanations.push(explanation);
+        }
+        explanations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{input::Input, navigation::Navigation};
+
+    use super::PackageExplanation;
+
+    fn create_input() -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 10, "B".to_string(), "C".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 6, "B".to_string()),
+                ("Q2".to_string(), 10, "C".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut navigation = Navigation::new(create_input());
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            navigation.explain(movements),
+            vec![
+                PackageExplanation {
+                    package: "K1".to_string(),
+                    train: "Q1".to_string(),
+                    makespan: 70,
+                    number_of_trains: 2,
+                    alternative_train: "Q2".to_string(),
+                    alternative_makespan: Some(100),
+                    alternative_number_of_trains: Some(1),
+                    makespan_difference: Some(30),
+                    infeasible_trains: vec![],
+                },
+                PackageExplanation {
+                    package: "K2".to_string(),
+                    train: "Q2".to_string(),
+                    makespan: 70,
+                    number_of_trains: 2,
+                    alternative_train: "".to_string(),
+                    alternative_makespan: None,
+                    alternative_number_of_trains: None,
+                    makespan_difference: None,
+                    infeasible_trains: vec![],
+                },
+            ]
+        );
+
+        // Q2 cannot go to A and back within its distance limit.
+        let mut navigation = Navigation::new(Input {
+            max_distances: vec![("Q2".to_string(), 50)],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            navigation.explain(movements)[0],
+            PackageExplanation {
+                package: "K1".to_string(),
+                train: "Q1".to_string(),
+                makespan: 70,
+                number_of_trains: 2,
+                alternative_train: "".to_string(),
+                alternative_makespan: None,
+                alternative_number_of_trains: None,
+                makespan_difference: None,
+                infeasible_trains: vec!["Q2".to_string()],
+            }
+        );
+    }
+}
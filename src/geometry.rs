@@ -0,0 +1,287 @@
+use crate::{graph::Graph, json::Json, navigation::Navigation};
+
+const EARTH_RADIUS: f64 = 6371.0;
+
+// Station position, either on a plane in the same unit as edge distances or
+// as latitude/longitude in degrees with edge distances in kilometres.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coordinates {
+    Planar { x: f64, y: f64 },
+    Geographic { latitude: f64, longitude: f64 },
+}
+
+impl Coordinates {
+    // Straight-line (great-circle for geographic) distance, `None` when the
+    // two positions are not of the same kind.
+    pub fn distance_to(&self, other: &Coordinates) -> Option<f64> {
+        match (self, other) {
+            (Coordinates::Planar { x: x1, y: y1 }, Coordinates::Planar { x: x2, y: y2 }) => {
+                Some(((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt())
+            }
+            (
+                Coordinates::Geographic {
+                    latitude: latitude1,
+                    longitude: longitude1,
+                },
+                Coordinates::Geographic {
+                    latitude: latitude2,
+                    longitude: longitude2,
+                },
+            ) => {
+                let latitude_delta = (latitude2 - latitude1).to_radians();
+                let longitude_delta = (longitude2 - longitude1).to_radians();
+                let a = (latitude_delta / 2.0).sin().powi(2)
+                    + latitude1.to_radians().cos()
+                        * latitude2.to_radians().cos()
+                        * (longitude_delta / 2.0).sin().powi(2);
+                Some(2.0 * EARTH_RADIUS * a.sqrt().asin())
+            }
+            _ => None,
+        }
+    }
+    // Position as (x, y), longitude first for geographic coordinates as in
+    // GeoJSON.
+    pub fn to_xy(&self) -> (f64, f64) {
+        match self {
+            Coordinates::Planar { x, y } => (*x, *y),
+            Coordinates::Geographic {
+                latitude,
+                longitude,
+            } => (*longitude, *latitude),
+        }
+    }
+}
+
+impl Graph {
+    pub fn has_all_coordinates(&self) -> bool {
+        self.station_ids
+            .values()
+            .all(|id| self.coordinates.contains_key(id))
+    }
+    // Groups the stations that have coordinates into `k` clusters with
+    // k-means, seeded with the stations furthest apart so the result does not
+    // depend on chance.
+    pub fn cluster_stations(&self, k: usize) -> Vec<Vec<String>> {
+        let mut ids: Vec<usize> = self.coordinates.keys().copied().collect();
+        ids.sort_by(|id_a, id_b| self.stations[*id_a].cmp(&self.stations[*id_b]));
+        if ids.is_empty() || k == 0 {
+            return vec![];
+        }
+
+        let position = |id: &usize| self.coordinates[id].to_xy();
+        let distance = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| {
+            ((x1 - x2).powi(2) + (y1 - y2).powi(2)).sqrt()
+        };
+
+        let mut centroids: Vec<(f64, f64)> = vec![position(&ids[0])];
+        while centroids.len() < k.min(ids.len()) {
+            let mut furthest = ids[0];
+            let mut furthest_distance = -1.0;
+            for id in ids.iter() {
+                let nearest = centroids
+                    .iter()
+                    .map(|centroid| distance(position(id), *centroid))
+                    .fold(f64::MAX, f64::min);
+                if nearest > furthest_distance {
+                    furthest = *id;
+                    furthest_distance = nearest;
+                }
+            }
+            centroids.push(position(&furthest));
+        }
+
+        let mut assignments: Vec<usize> = vec![0; ids.len()];
+        for _ in 0..100 {
+            let mut changed = false;
+            for (i, id) in ids.iter().enumerate() {
+                let mut nearest = 0;
+                for (j, centroid) in centroids.iter().enumerate() {
+                    if distance(position(id), *centroid)
+                        < distance(position(id), centroids[nearest])
+                    {
+                        nearest = j;
+                    }
+                }
+                if assignments[i] != nearest {
+                    assignments[i] = nearest;
+                    changed = true;
+                }
+            }
+            for (j, centroid) in centroids.iter_mut().enumerate() {
+                let members: Vec<(f64, f64)> = ids
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| assignments[*i] == j)
+                    .map(|(_, id)| position(id))
+                    .collect();
+                if !members.is_empty() {
+                    let count = members.len() as f64;
+                    *centroid = (
+                        members.iter().map(|(x, _)| x).sum::<f64>() / count,
+                        members.iter().map(|(_, y)| y).sum::<f64>() / count,
+                    );
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut clusters: Vec<Vec<String>> = vec![vec![]; centroids.len()];
+        for (i, id) in ids.iter().enumerate() {
+            clusters[assignments[i]].push(self.stations[*id].clone());
+        }
+        clusters.retain(|cluster| !cluster.is_empty());
+        clusters
+    }
+}
+
+impl Navigation {
+    // GeoJSON with a point per station and a line per edge between stations
+    // that both have coordinates.
+    pub fn to_geojson(&self) -> String {
+        let mut features: Vec<Json> = vec![];
+        for (id, station) in self.graph.stations.iter().enumerate() {
+            if let Some(coordinates) = self.graph.coordinates.get(&id) {
+                let (x, y) = coordinates.to_xy();
+                features.push(Json::Object(vec![
+                    ("type".to_string(), Json::from("Feature")),
+                    (
+                        "geometry".to_string(),
+                        Json::Object(vec![
+                            ("type".to_string(), Json::from("Point")),
+                            (
+                                "coordinates".to_string(),
+                                Json::Array(vec![Json::Number(x), Json::Number(y)]),
+                            ),
+                        ]),
+                    ),
+                    (
+                        "properties".to_string(),
+                        Json::Object(vec![("name".to_string(), Json::from(station.clone()))]),
+                    ),
+                ]));
+            }
+        }
+
+        let mut edges: Vec<(String, (String, String, i32))> =
+            self.edges.clone().into_iter().collect();
+        edges.sort_by(|(edge_a, _), (edge_b, _)| edge_a.cmp(edge_b));
+        for (name, (from, to, distance)) in edges {
+            let from_coordinates = self
+                .graph
+                .get_station_id(&from)
+                .and_then(|id| self.graph.coordinates.get(&id));
+            let to_coordinates = self
+                .graph
+                .get_station_id(&to)
+                .and_then(|id| self.graph.coordinates.get(&id));
+            if let (Some(from_coordinates), Some(to_coordinates)) =
+                (from_coordinates, to_coordinates)
+            {
+                let (x1, y1) = from_coordinates.to_xy();
+                let (x2, y2) = to_coordinates.to_xy();
+                features.push(Json::Object(vec![
+                    ("type".to_string(), Json::from("Feature")),
+                    (
+                        "geometry".to_string(),
+                        Json::Object(vec![
+                            ("type".to_string(), Json::from("LineString")),
+                            (
+                                "coordinates".to_string(),
+                                Json::Array(vec![
+                                    Json::Array(vec![Json::Number(x1), Json::Number(y1)]),
+                                    Json::Array(vec![Json::Number(x2), Json::Number(y2)]),
+                                ]),
+                            ),
+                        ]),
+                    ),
+                    (
+                        "properties".to_string(),
+                        Json::Object(vec![
+                            ("name".to_string(), Json::from(name)),
+                            ("from".to_string(), Json::from(from)),
+                            ("to".to_string(), Json::from(to)),
+                            ("distance".to_string(), Json::from(distance)),
+                        ]),
+                    ),
+                ]));
+            }
+        }
+
+        Json::Object(vec![
+            ("type".to_string(), Json::from("FeatureCollection")),
+            ("features".to_string(), Json::Array(features)),
+        ])
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{graph::Graph, input::Input, navigation::Navigation};
+
+    use super::Coordinates;
+
+    #[test]
+    fn test_coordinates_distance() {
+        let a = Coordinates::Planar { x: 0.0, y: 0.0 };
+        let b = Coordinates::Planar { x: 3.0, y: 4.0 };
+        assert_eq!(a.distance_to(&b), Some(5.0));
+
+        let kuala_lumpur = Coordinates::Geographic {
+            latitude: 3.139,
+            longitude: 101.6869,
+        };
+        let singapore = Coordinates::Geographic {
+            latitude: 1.3521,
+            longitude: 103.8198,
+        };
+        let distance = kuala_lumpur.distance_to(&singapore).unwrap();
+        assert!((distance - 309.0).abs() < 5.0);
+        assert_eq!(a.distance_to(&singapore), None);
+    }
+
+    #[test]
+    fn test_cluster_stations() {
+        let mut graph = Graph::new();
+        for (station, x, y) in [
+            ("A", 0.0, 0.0),
+            ("B", 1.0, 0.0),
+            ("C", 0.0, 1.0),
+            ("X", 100.0, 100.0),
+            ("Y", 101.0, 100.0),
+        ] {
+            graph.set_coordinates(station.to_string(), Coordinates::Planar { x, y });
+        }
+        assert_eq!(
+            graph.cluster_stations(2),
+            vec![
+                vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                vec!["X".to_string(), "Y".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_geojson() {
+        let navigation = Navigation::new(Input {
+            edges: vec![("E1".to_string(), "A".to_string(), "B".to_string(), 30)],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "B".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            stations: vec![
+                ("A".to_string(), Coordinates::Planar { x: 0.0, y: 0.0 }),
+                ("B".to_string(), Coordinates::Planar { x: 30.0, y: 0.0 }),
+            ],
+            ..Default::default()
+        });
+        assert_eq!(
+            navigation.to_geojson(),
+            "{\"type\":\"FeatureCollection\",\"features\":[\
+             {\"type\":\"Feature\",\"geometry\":{\"type\":\"Point\",\"coordinates\":[0,0]},\"properties\":{\"name\":\"A\"}},\
+             {\"type\":\"Feature\",\"geometry\":{\"type\":\"Point\",\"coordinates\":[30,0]},\"properties\":{\"name\":\"B\"}},\
+             {\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[0,0],[30,0]]},\
+             \"properties\":{\"name\":\"E1\",\"from\":\"A\",\"to\":\"B\",\"distance\":30}}]}"
+        );
+    }
+}
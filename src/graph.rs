@@ -1,204 +1,392 @@
-use std::{
-    collections::{HashMap, HashSet},
-    i32,
+use std::collections::HashMap;
+
+use crate::{
+    a_star::{CoordinateHeuristic, ZeroHeuristic},
+    destination::Destination,
+    geometry::Coordinates,
+    min_heap::MinHeap,
+    route::Route,
+    tie_break::TieBreak,
 };
 
-use crate::{destination::Destination, min_heap::MinHeap, route::Route};
+// Shortest path tree from one source, indexed by station ID. Unreachable
+// stations have a distance of `i32::MAX` and no previous station.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortestPaths {
+    pub distances: Vec<i32>,
+    pub previous: Vec<Option<usize>>,
+}
 
+#[derive(Clone)]
 pub struct Graph {
-    pub adj_list: HashMap<String, Vec<Route>>,
-    pub cache: HashMap<String, HashMap<String, Destination>>,
+    // Interned station names, a station's ID is its index.
+    pub stations: Vec<String>,
+    pub station_ids: HashMap<String, usize>,
+    // Undirected edges as (from, to, distance, name), the adjacency is built
+    // from these. The name is "" for edges added without one.
+    pub edges: Vec<(usize, usize, i32, String)>,
+    // CSR adjacency: the neighbours of station `i` are
+    // `targets[offsets[i]..offsets[i + 1]]`, reached over `edge_ids`, the
+    // index in `edges` of each.
+    pub offsets: Vec<usize>,
+    pub targets: Vec<usize>,
+    pub distances: Vec<i32>,
+    pub edge_ids: Vec<usize>,
+    // Set by every change to the stations or edges, the adjacency is rebuilt
+    // on the next query.
+    pub adjacency_dirty: bool,
+    pub cache: HashMap<usize, ShortestPaths>,
+    // Maintenance windows per edge, as [start, end) times in which no train
+    // may be travelling on it.
+    pub closures: HashMap<(usize, usize), Vec<(i32, i32)>>,
+    // Station positions, used by the A* heuristics.
+    pub coordinates: HashMap<usize, Coordinates>,
+    // Whether no edge is shorter than the straight line between its
+    // stations, checked as edges and coordinates are added. The straight
+    // line only guides A* while this holds.
+    pub coordinates_admissible: bool,
+    pub tie_break: TieBreak,
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Graph {
     pub fn new() -> Graph {
         Graph {
-            adj_list: HashMap::new(),
+            stations: vec![],
+            station_ids: HashMap::new(),
+            edges: vec![],
+            offsets: vec![0],
+            targets: vec![],
+            distances: vec![],
+            edge_ids: vec![],
+            adjacency_dirty: false,
             cache: HashMap::new(),
+            closures: HashMap::new(),
+            coordinates: HashMap::new(),
+            coordinates_admissible: true,
+            tie_break: TieBreak::default(),
+        }
+    }
+    pub fn add_station(&mut self, name: String) -> usize {
+        if let Some(id) = self.station_ids.get(&name) {
+            return *id;
+        }
+        let id = self.stations.len();
+        self.stations.push(name.clone());
+        self.station_ids.insert(name, id);
+        self.invalidate_adjacency();
+        // The new station is unreachable until an edge is added.
+        for shortest_paths in self.cache.values_mut() {
+            shortest_paths.distances.push(i32::MAX);
+            shortest_paths.previous.push(None);
         }
+        id
+    }
+    pub fn get_station_id(&self, name: &str) -> Option<usize> {
+        self.station_ids.get(name).copied()
     }
     pub fn add_edge(&mut self, from: String, to: String, distance: i32) {
-        self.adj_list
-            .entry(from.clone())
-            .and_modify(|froms| {
-                froms.push(Route {
-                    to: to.clone(),
-                    distance: distance,
-                })
-            })
-            .or_insert(
-                [Route {
-                    to: to.clone(),
-                    distance: distance,
-                }]
-                .to_vec(),
-            );
-        self.adj_list
-            .entry(to.clone())
-            .and_modify(|tos| {
-                tos.push(Route {
-                    to: from.clone(),
-                    distance: distance,
-                })
-            })
-            .or_insert(
-                [Route {
-                    to: from.clone(),
-                    distance: distance,
-                }]
-                .to_vec(),
-            );
+        self.add_named_edge("".to_string(), from, to, distance);
     }
-    pub fn calculate_neighbour(
-        neighbour: Route,
-        start: String,
-        current: String,
-        current_distance: i32,
-        destinations: HashMap<String, Destination>,
-    ) -> Option<Destination> {
-        let next = neighbour.to.clone();
-        let distance = neighbour.distance;
-
-        let new_cumulative_distance = current_distance + distance;
-        let cumulative_distance: i32;
-        let mut prev_destination: Destination = Destination::new();
-        let mut prev_checkpoints: Vec<Route> = [].to_vec();
-        match destinations.get(&next) {
-            Some(destination) => {
-                cumulative_distance = destination.cumulative_distance;
+    pub fn add_named_edge(&mut self, name: String, from: String, to: String, distance: i32) {
+        let from_id = self.add_station(from);
+        let to_id = self.add_station(to);
+        self.edges.push((from_id, to_id, distance, name));
+        self.invalidate_adjacency();
+        self.invalidate_edge(from_id, to_id, false, Some(distance));
+        self.check_admissible(from_id, to_id, distance);
+    }
+    // Clears `coordinates_admissible` when the edge is shorter than the
+    // straight line between its stations.
+    pub fn check_admissible(&mut self, from: usize, to: usize, distance: i32) {
+        let straight_line_distance = match (self.coordinates.get(&from), self.coordinates.get(&to))
+        {
+            (Some(from_coordinates), Some(to_coordinates)) => {
+                from_coordinates.distance_to(to_coordinates)
+            }
+            _ => None,
+        };
+        if let Some(straight_line_distance) = straight_line_distance {
+            if (distance as f64) < straight_line_distance {
+                self.coordinates_admissible = false;
             }
-            None => cumulative_distance = i32::MAX,
         }
-        match destinations.get(&current) {
-            Some(dest) => {
-                prev_destination = dest.clone();
-                prev_checkpoints = prev_destination.checkpoints.clone();
+    }
+    pub fn invalidate_adjacency(&mut self) {
+        self.adjacency_dirty = true;
+    }
+    // Rebuilds the CSR adjacency from `edges`. Queries call this themselves,
+    // it only needs calling directly before using `get_neighbours`.
+    pub fn build_adjacency(&mut self) {
+        if !self.adjacency_dirty {
+            return;
+        }
+        let mut degrees = vec![0; self.stations.len()];
+        for (from, to, _, _) in self.edges.iter() {
+            degrees[*from] += 1;
+            degrees[*to] += 1;
+        }
+        let mut offsets = vec![0; self.stations.len() + 1];
+        for (id, degree) in degrees.iter().enumerate() {
+            offsets[id + 1] = offsets[id] + degree;
+        }
+        let mut next = offsets.clone();
+        let mut targets = vec![0; self.edges.len() * 2];
+        let mut distances = vec![0; self.edges.len() * 2];
+        let mut edge_ids = vec![0; self.edges.len() * 2];
+        for (edge_id, (from, to, distance, _)) in self.edges.iter().enumerate() {
+            for (a, b) in [(*from, *to), (*to, *from)] {
+                targets[next[a]] = b;
+                distances[next[a]] = *distance;
+                edge_ids[next[a]] = edge_id;
+                next[a] += 1;
             }
-            None => {}
         }
-
-        if new_cumulative_distance < cumulative_distance {
-            let mut checkpoints = [].to_vec();
-            if prev_destination.checkpoints.len() > 0 {
-                checkpoints = prev_checkpoints.clone();
-                checkpoints.push(Route {
-                    to: current.clone(),
-                    distance: current_distance
-                        - prev_checkpoints[prev_checkpoints.len() - 1].distance,
-                })
-            } else if current != start {
-                checkpoints.push(Route {
-                    to: current.clone(),
-                    distance: current_distance,
-                });
+        self.offsets = offsets;
+        self.targets = targets;
+        self.distances = distances;
+        self.edge_ids = edge_ids;
+        self.adjacency_dirty = false;
+    }
+    pub fn get_neighbours(&self, id: usize) -> impl Iterator<Item = (usize, i32)> + '_ {
+        let range = self.offsets[id]..self.offsets[id + 1];
+        self.targets[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.distances[range].iter().copied())
+    }
+    // The name of the edge a path took between the two stations, looked up
+    // in the adjacency of `from`. Parallel edges of the same distance are
+    // alike to every search, so the one added first is the one taken.
+    pub fn get_edge_name(&self, from: usize, to: usize, distance: i32) -> String {
+        (self.offsets[from]..self.offsets[from + 1])
+            .find(|i| self.targets[*i] == to && self.distances[*i] == distance)
+            .map(|i| self.edges[self.edge_ids[i]].3.clone())
+            .unwrap_or_default()
+    }
+    pub fn set_coordinates(&mut self, station: String, coordinates: Coordinates) {
+        let id = self.add_station(station);
+        self.coordinates.insert(id, coordinates);
+        self.check_station_admissible(id);
+    }
+    pub fn check_station_admissible(&mut self, id: usize) {
+        self.build_adjacency();
+        let neighbours: Vec<(usize, i32)> = self.get_neighbours(id).collect();
+        for (neighbour, distance) in neighbours {
+            self.check_admissible(id, neighbour, distance);
+        }
+    }
+    pub fn add_closure(&mut self, from: String, to: String, start: i32, end: i32) {
+        let from_id = self.add_station(from);
+        let to_id = self.add_station(to);
+        for key in [(from_id, to_id), (to_id, from_id)] {
+            let windows = self.closures.entry(key).or_default();
+            windows.push((start, end));
+            windows.sort();
+        }
+    }
+    pub fn get_departure_time(&self, from: usize, to: usize, distance: i32, time: i32) -> i32 {
+        let mut departure_time = time;
+        if let Some(windows) = self.closures.get(&(from, to)) {
+            for (start, end) in windows {
+                if departure_time < *end && departure_time + distance > *start {
+                    departure_time = *end;
+                }
             }
-            return Some(Destination {
-                from: start.clone(),
-                to: next.clone(),
-                checkpoints,
-                distance,
-                cumulative_distance: new_cumulative_distance,
-            });
         }
-        None
+        departure_time
     }
-    pub fn dijkstra(&mut self, start: String) -> HashMap<String, Destination> {
-        let mut destinations = match self.cache.get(&start) {
-            Some(cached_desttinations) => cached_desttinations.clone(),
-            None => HashMap::new(),
-        };
+    pub fn shortest_paths(&mut self, source: usize) -> &ShortestPaths {
+        if !self.cache.contains_key(&source) {
+            self.build_adjacency();
+            let mut distances = vec![i32::MAX; self.stations.len()];
+            let mut previous: Vec<Option<usize>> = vec![None; self.stations.len()];
+            let mut min_heap: MinHeap<usize, i32> = MinHeap::new();
 
-        if destinations.len() > 0 {
-            return destinations;
-        }
+            distances[source] = 0;
+            min_heap.push(source, 0);
 
-        let mut min_heap = MinHeap::new();
-        let mut visited: HashSet<String> = HashSet::new();
+            while let Some((current, current_distance)) = min_heap.pop() {
+                for (next, distance) in self.get_neighbours(current) {
+                    let new_distance = current_distance + distance;
+                    if new_distance < distances[next]
+                        || (new_distance == distances[next]
+                            && self.is_better_tie(next, current, &|station| previous[station]))
+                    {
+                        distances[next] = new_distance;
+                        previous[next] = Some(current);
+                        min_heap.push(next, new_distance);
+                    }
+                }
+            }
 
-        for (to, _route) in self.adj_list.iter() {
-            destinations.insert(
-                start.clone(),
-                Destination {
-                    from: start.clone(),
-                    to: to.clone(),
-                    checkpoints: [].to_vec(),
-                    distance: 0,
-                    cumulative_distance: i32::MAX,
+            self.cache.insert(
+                source,
+                ShortestPaths {
+                    distances,
+                    previous,
                 },
             );
         }
-
-        destinations.insert(
-            start.clone(),
-            Destination {
-                from: start.clone(),
-                to: start.clone(),
-                checkpoints: [].to_vec(),
-                distance: 0,
-                cumulative_distance: 0,
-            },
-        );
-
-        min_heap.add(Route {
-            to: start.clone(),
-            distance: 0,
-        });
-
-        while min_heap.heap.len() > 0 {
-            let current: String;
-            let current_distance: i32;
-            match MinHeap::remove(&mut min_heap) {
-                Some(min) => {
-                    current = min.to;
-                    current_distance = min.distance;
-                }
-                None => {
-                    panic!("Minimum distance not found");
-                }
+        &self.cache[&source]
+    }
+    // Builds a `Destination` from the legs of a path, each given as the
+    // station it arrives at, its distance and the wait before it. The
+    // adjacency has to be built.
+    pub fn build_destination(
+        &self,
+        from: usize,
+        to: usize,
+        legs: Vec<(usize, i32, i32)>,
+    ) -> Destination {
+        let mut destination = Destination {
+            from: self.stations[from].clone(),
+            to: self.stations[to].clone(),
+            ..Destination::new()
+        };
+        let mut previous = from;
+        for (i, (station, distance, wait)) in legs.iter().enumerate() {
+            destination.cumulative_distance += distance;
+            destination.waits.push(*wait);
+            let edge = self.get_edge_name(previous, *station, *distance);
+            if i < legs.len() - 1 {
+                destination.checkpoints.push(Route {
+                    to: self.stations[*station].clone(),
+                    distance: *distance,
+                    edge,
+                });
+            } else {
+                destination.distance = *distance;
+                destination.edge = edge;
             }
-
-            if visited.contains(&current) {
-                continue;
+            previous = *station;
+        }
+        if destination.waits.iter().all(|wait| *wait == 0) {
+            destination.waits = vec![];
+        }
+        destination
+    }
+    pub fn get_destination_by_id(&mut self, from: usize, to: usize) -> Option<Destination> {
+        // The edge names come from the adjacency, which a cached tree does not
+        // need.
+        self.build_adjacency();
+        let shortest_paths = self.shortest_paths(from);
+        if shortest_paths
+            .distances
+            .get(to)
+            .is_none_or(|distance| *distance == i32::MAX)
+        {
+            return None;
+        }
+        let mut legs: Vec<(usize, i32, i32)> = vec![];
+        let mut station = to;
+        while let Some(prev) = shortest_paths.previous[station] {
+            legs.push((
+                station,
+                shortest_paths.distances[station] - shortest_paths.distances[prev],
+                0,
+            ));
+            station = prev;
+        }
+        legs.reverse();
+        Some(self.build_destination(from, to, legs))
+    }
+    pub fn dijkstra(&mut self, start: String) -> HashMap<String, Destination> {
+        let mut destinations: HashMap<String, Destination> = HashMap::new();
+        let start_id = match self.get_station_id(&start) {
+            Some(id) => id,
+            None => return destinations,
+        };
+        for id in 0..self.stations.len() {
+            if let Some(destination) = self.get_destination_by_id(start_id, id) {
+                destinations.insert(self.stations[id].clone(), destination);
             }
+        }
+        destinations
+    }
+    // Earliest arrival from `from` to `to` when leaving at `departure_time`,
+    // waiting out closures or detouring around them, whichever is quicker.
+    pub fn get_destination_at(
+        &mut self,
+        from: String,
+        to: String,
+        departure_time: i32,
+    ) -> Destination {
+        if self.closures.is_empty() {
+            return Graph::get_destination(self, from, to);
+        }
+        let (from_id, to_id) = match (self.get_station_id(&from), self.get_station_id(&to)) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            _ => panic!("Destination not found"),
+        };
+        self.build_adjacency();
 
-            visited.insert(current.clone());
-
-            let mut neighbours: Vec<Route> = [].to_vec();
-            match self.adj_list.get(&current) {
-                Some(routes) => {
-                    neighbours = routes.clone();
-                }
-                None => {}
-            }
+        let mut arrival_times = vec![i32::MAX; self.stations.len()];
+        let mut previous: Vec<Option<(usize, i32, i32)>> = vec![None; self.stations.len()];
+        let mut min_heap: MinHeap<usize, i32> = MinHeap::new();
 
-            for neighbour in neighbours {
-                let destination_option = Graph::calculate_neighbour(
-                    neighbour,
-                    start.clone(),
-                    current.clone(),
-                    current_distance,
-                    destinations.clone(),
-                );
+        arrival_times[from_id] = departure_time;
+        min_heap.push(from_id, departure_time);
 
-                match destination_option {
-                    Some(destination) => {
-                        destinations.insert(destination.to.clone(), destination.clone());
-                        min_heap.add(Route {
-                            to: destination.to.clone(),
-                            distance: destination.cumulative_distance,
-                        });
-                    }
-                    None => {}
+        while let Some((current, current_time)) = min_heap.pop() {
+            if current == to_id {
+                break;
+            }
+            for (next, distance) in self.get_neighbours(current) {
+                let leg_departure_time =
+                    self.get_departure_time(current, next, distance, current_time);
+                let arrival_time = leg_departure_time + distance;
+                if arrival_time < arrival_times[next]
+                    || (arrival_time == arrival_times[next]
+                        && self.is_better_tie(next, current, &|station| {
+                            previous[station].map(|(prev, _, _)| prev)
+                        }))
+                {
+                    arrival_times[next] = arrival_time;
+                    previous[next] = Some((current, distance, leg_departure_time - current_time));
+                    min_heap.push(next, arrival_time);
                 }
             }
         }
 
-        self.cache.insert(start.clone(), destinations.clone());
-        return destinations;
+        if arrival_times[to_id] == i32::MAX {
+            panic!("Destination not found");
+        }
+
+        let mut legs: Vec<(usize, i32, i32)> = vec![];
+        let mut station = to_id;
+        while let Some((prev, distance, wait)) = previous[station] {
+            legs.push((station, distance, wait));
+            station = prev;
+        }
+        legs.reverse();
+        self.build_destination(from_id, to_id, legs)
     }
+    // Uses the cached shortest path tree of `from` when there is one, and a
+    // point-to-point A* search otherwise, guided by the straight line when
+    // every station has coordinates and no edge is shorter than it.
     pub fn get_destination(&mut self, from: String, to: String) -> Destination {
-        match Graph::dijkstra(self, from).get(&to) {
-            Some(dest) => dest.clone(),
+        let destination = match (self.get_station_id(&from), self.get_station_id(&to)) {
+            (Some(from_id), Some(to_id)) if self.cache.contains_key(&from_id) => {
+                self.get_destination_by_id(from_id, to_id)
+            }
+            _ if self.coordinates_admissible && self.has_all_coordinates() => {
+                self.build_adjacency();
+                let heuristic = CoordinateHeuristic::new(self);
+                self.a_star(from, to, &heuristic)
+            }
+            _ => {
+                self.build_adjacency();
+                self.a_star(from, to, &ZeroHeuristic)
+            }
+        };
+        match destination {
+            Some(dest) => dest,
             None => {
                 panic!("Destination not found");
             }
@@ -227,22 +415,126 @@ mod tests {
                 from: "A".to_string(),
                 to: "D".to_string(),
                 distance: 10,
+                edge: "".to_string(),
                 cumulative_distance: 40,
                 checkpoints: [
                     Route {
                         to: "C".to_string(),
                         distance: 10,
+                        edge: "".to_string(),
                     },
                     Route {
                         to: "B".to_string(),
                         distance: 20,
+                        edge: "".to_string(),
                     }
                 ]
-                .to_vec()
+                .to_vec(),
+                waits: [].to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_graph_closure() {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 10);
+        graph.add_edge("B".to_string(), "C".to_string(), 10);
+        graph.add_edge("A".to_string(), "D".to_string(), 15);
+        graph.add_edge("D".to_string(), "C".to_string(), 15);
+        graph.add_closure("B".to_string(), "C".to_string(), 5, 15);
+        let destination = graph.get_destination_at("A".to_string(), "C".to_string(), 0);
+        assert_eq!(
+            destination,
+            Destination {
+                from: "A".to_string(),
+                to: "C".to_string(),
+                distance: 10,
+                edge: "".to_string(),
+                cumulative_distance: 20,
+                checkpoints: [Route {
+                    to: "B".to_string(),
+                    distance: 10,
+                    edge: "".to_string(),
+                }]
+                .to_vec(),
+                waits: [0, 5].to_vec(),
+            }
+        );
+
+        graph.add_closure("B".to_string(), "C".to_string(), 15, 100);
+        let destination = graph.get_destination_at("A".to_string(), "C".to_string(), 0);
+        assert_eq!(
+            destination,
+            Destination {
+                from: "A".to_string(),
+                to: "C".to_string(),
+                distance: 15,
+                edge: "".to_string(),
+                cumulative_distance: 30,
+                checkpoints: [Route {
+                    to: "D".to_string(),
+                    distance: 15,
+                    edge: "".to_string(),
+                }]
+                .to_vec(),
+                waits: [].to_vec(),
             }
         );
     }
 
+    #[test]
+    fn test_graph_checkpoints() {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 5);
+        graph.add_edge("B".to_string(), "C".to_string(), 10);
+        graph.add_edge("C".to_string(), "D".to_string(), 15);
+        graph.add_edge("D".to_string(), "E".to_string(), 20);
+        graph.add_edge("A".to_string(), "E".to_string(), 100);
+        let destination = graph.get_destination("A".to_string(), "E".to_string());
+        assert_eq!(destination.cumulative_distance, 50);
+        assert_eq!(destination.distance, 20);
+        assert_eq!(
+            destination.checkpoints,
+            [
+                Route {
+                    to: "B".to_string(),
+                    distance: 5,
+                    edge: "".to_string(),
+                },
+                Route {
+                    to: "C".to_string(),
+                    distance: 10,
+                    edge: "".to_string(),
+                },
+                Route {
+                    to: "D".to_string(),
+                    distance: 15,
+                    edge: "".to_string(),
+                },
+            ]
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_graph_many_stations() {
+        let mut graph = Graph::new();
+        for i in 0..50_000 {
+            graph.add_edge(format!("S{}", i), format!("S{}", i + 1), 1);
+            graph.add_edge(format!("S{}", i), format!("S{}", (i * 7) % 50_000), 10);
+        }
+        let destination = graph.get_destination("S0".to_string(), "S50000".to_string());
+        assert_eq!(destination.from, "S0");
+        assert_eq!(destination.to, "S50000");
+        assert!(destination.cumulative_distance <= 50_000);
+        let shortest_paths = graph.shortest_paths(0);
+        assert!(shortest_paths
+            .distances
+            .iter()
+            .all(|distance| *distance != i32::MAX));
+    }
+
     #[test]
     #[should_panic]
     fn test_graph_failure() {
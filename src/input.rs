@@ -1,5 +1,50 @@
+use crate::{cost::Objective, geometry::Coordinates, tie_break::TieBreak};
+
+#[derive(Default)]
 pub struct Input {
     pub edges: Vec<(String, String, String, i32)>,
     pub packages: Vec<(String, i32, String, String)>,
     pub trains: Vec<(String, i32, String)>,
+    // Maintenance windows as (edge, start, end).
+    pub closures: Vec<(String, i32, i32)>,
+    // Optional station positions.
+    pub stations: Vec<(String, Coordinates)>,
+    // How to choose between shortest paths of the same length.
+    pub tie_break: TieBreak,
+    // (package, train) pairs: the package has to go on that train, or may
+    // never go on it.
+    pub must_assign: Vec<(String, String)>,
+    pub must_not_assign: Vec<(String, String)>,
+    // (package, attribute) the package requires and (train, attribute) the
+    // train supports, such as "refrigerated" or "hazardous".
+    pub package_attributes: Vec<(String, String)>,
+    pub train_attributes: Vec<(String, String)>,
+    // Pairs of attributes whose packages may not travel on the same train.
+    pub incompatible_attributes: Vec<(String, String)>,
+    // Names of the capacity dimensions beyond weight, such as "volume" or
+    // "slots", with (train, dimension, capacity) and (package, dimension,
+    // size). Capacities not given are unlimited and sizes not given are 0.
+    pub dimensions: Vec<String>,
+    pub train_capacities: Vec<(String, String, i32)>,
+    pub package_sizes: Vec<(String, String, i32)>,
+    // (train, station) the train has to finish its day at.
+    pub train_ends: Vec<(String, String)>,
+    // Trains without an end station have to finish where they started.
+    pub return_to_start: bool,
+    // (train, limit) for the longest the train may operate, from its first
+    // departure to its last arrival, and the furthest it may travel.
+    pub max_operating_times: Vec<(String, i32)>,
+    pub max_distances: Vec<(String, i32)>,
+    // (train, driving time, break) for a break the train has to take once
+    // it has driven that long.
+    pub rest_breaks: Vec<(String, i32, i32)>,
+    // (train, time) for trains busy with other duties until then. Other
+    // trains can leave at 0.
+    pub train_departures: Vec<(String, i32)>,
+    // Stations where a train can leave a package for another train.
+    pub hubs: Vec<String>,
+    // (train, fixed cost, cost per distance) and (edge, toll).
+    pub train_costs: Vec<(String, i32, i32)>,
+    pub tolls: Vec<(String, i32)>,
+    pub objective: Objective,
 }
@@ -0,0 +1,342 @@
+use std::fmt;
+
+// Arrays and objects nested deeper than this are rejected, so a hostile body
+// cannot exhaust the stack.
+const MAX_DEPTH: usize = 128;
+
+// Minimal JSON value, enough for the exports and the server without pulling
+// in a serialisation crate. Object keys keep their insertion order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn escape(value: &str) -> String {
+        let mut escaped = String::new();
+        for character in value.chars() {
+            match character {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                character if (character as u32) < 0x20 => {
+                    escaped.push_str(&format!("\\u{:04x}", character as u32))
+                }
+                character => escaped.push(character),
+            }
+        }
+        escaped
+    }
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let characters: Vec<char> = text.chars().collect();
+        let mut position = 0;
+        let json = Json::parse_value(&characters, &mut position, 0)?;
+        Json::skip_whitespace(&characters, &mut position);
+        if position < characters.len() {
+            return Err(format!("Unexpected character at {}", position));
+        }
+        Ok(json)
+    }
+    fn skip_whitespace(characters: &[char], position: &mut usize) {
+        while *position < characters.len() && characters[*position].is_whitespace() {
+            *position += 1;
+        }
+    }
+    fn expect(characters: &[char], position: &mut usize, expected: &str) -> Result<(), String> {
+        for character in expected.chars() {
+            if characters.get(*position) != Some(&character) {
+                return Err(format!("Expected '{}' at {}", character, position));
+            }
+            *position += 1;
+        }
+        Ok(())
+    }
+    fn parse_value(
+        characters: &[char],
+        position: &mut usize,
+        depth: usize,
+    ) -> Result<Json, String> {
+        Json::skip_whitespace(characters, position);
+        if depth >= MAX_DEPTH && matches!(characters.get(*position), Some('[') | Some('{')) {
+            return Err(format!("Nested deeper than {} at {}", MAX_DEPTH, position));
+        }
+        match characters.get(*position) {
+            Some('n') => Json::expect(characters, position, "null").map(|_| Json::Null),
+            Some('t') => Json::expect(characters, position, "true").map(|_| Json::Bool(true)),
+            Some('f') => Json::expect(characters, position, "false").map(|_| Json::Bool(false)),
+            Some('"') => Json::parse_string(characters, position).map(Json::String),
+            Some('[') => {
+                *position += 1;
+                let mut values: Vec<Json> = vec![];
+                Json::skip_whitespace(characters, position);
+                if characters.get(*position) == Some(&']') {
+                    *position += 1;
+                    return Ok(Json::Array(values));
+                }
+                loop {
+                    values.push(Json::parse_value(characters, position, depth + 1)?);
+                    Json::skip_whitespace(characters, position);
+                    match characters.get(*position) {
+                        Some(',') => *position += 1,
+                        Some(']') => {
+                            *position += 1;
+                            return Ok(Json::Array(values));
+                        }
+                        _ => return Err(format!("Expected ',' or ']' at {}", position)),
+                    }
+                }
+            }
+            Some('{') => {
+                *position += 1;
+                let mut entries: Vec<(String, Json)> = vec![];
+                Json::skip_whitespace(characters, position);
+                if characters.get(*position) == Some(&'}') {
+                    *position += 1;
+                    return Ok(Json::Object(entries));
+                }
+                loop {
+                    Json::skip_whitespace(characters, position);
+                    let key = Json::parse_string(characters, position)?;
+                    Json::skip_whitespace(characters, position);
+                    Json::expect(characters, position, ":")?;
+                    entries.push((key, Json::parse_value(characters, position, depth + 1)?));
+                    Json::skip_whitespace(characters, position);
+                    match characters.get(*position) {
+                        Some(',') => *position += 1,
+                        Some('}') => {
+                            *position += 1;
+                            return Ok(Json::Object(entries));
+                        }
+                        _ => return Err(format!("Expected ',' or '}}' at {}", position)),
+                    }
+                }
+            }
+            Some(character) if *character == '-' || character.is_ascii_digit() => {
+                let start = *position;
+                while *position < characters.len()
+                    && (characters[*position].is_ascii_digit()
+                        || "+-.eE".contains(characters[*position]))
+                {
+                    *position += 1;
+                }
+                let number: String = characters[start..*position].iter().collect();
+                match number.parse::<f64>() {
+                    Ok(number) => Ok(Json::Number(number)),
+                    Err(_) => Err(format!("Invalid number at {}", start)),
+                }
+            }
+            _ => Err(format!("Unexpected character at {}", position)),
+        }
+    }
+    fn parse_string(characters: &[char], position: &mut usize) -> Result<String, String> {
+        Json::expect(characters, position, "\"")?;
+        let mut value = String::new();
+        loop {
+            match characters.get(*position) {
+                Some('"') => {
+                    *position += 1;
+                    return Ok(value);
+                }
+                Some('\\') => {
+                    *position += 1;
+                    match characters.get(*position) {
+                        Some('"') => value.push('"'),
+                        Some('\\') => value.push('\\'),
+                        Some('/') => value.push('/'),
+                        Some('b') => value.push('\u{8}'),
+                        Some('f') => value.push('\u{c}'),
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some('u') => {
+                            let hex: String = characters
+                                .get(*position + 1..*position + 5)
+                                .unwrap_or(&[])
+                                .iter()
+                                .collect();
+                            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                Some(character) => value.push(character),
+                                None => return Err(format!("Invalid escape at {}", position)),
+                            }
+                            *position += 4;
+                        }
+                        _ => return Err(format!("Invalid escape at {}", position)),
+                    }
+                    *position += 1;
+                }
+                Some(character) => {
+                    value.push(*character);
+                    *position += 1;
+                }
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+    // `None` for numbers with a fraction or out of range, rather than
+    // truncating them.
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_f64()
+            .filter(|value| {
+                value.fract() == 0.0 && *value >= i32::MIN as f64 && *value <= i32::MAX as f64
+            })
+            .map(|value| value as i32)
+    }
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for Json {
+    fn from(value: i32) -> Json {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Json {
+        Json::String(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Json {
+        Json::Bool(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        match value {
+            Some(value) => value.into(),
+            None => Json::Null,
+        }
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(values: Vec<T>) -> Json {
+        Json::Array(values.into_iter().map(|value| value.into()).collect())
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => {
+                if value.is_finite() {
+                    write!(f, "{}", value)
+                } else {
+                    write!(f, "null")
+                }
+            }
+            Json::String(value) => write!(f, "\"{}\"", Json::escape(value)),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", Json::escape(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn test_json_to_string() {
+        let json = Json::Object(vec![
+            ("name".to_string(), Json::from("Q\"1\"")),
+            ("capacity".to_string(), Json::from(15)),
+            ("distances".to_string(), Json::from(vec![Some(1), None])),
+            ("active".to_string(), Json::from(true)),
+        ]);
+        assert_eq!(
+            json.to_string(),
+            "{\"name\":\"Q\\\"1\\\"\",\"capacity\":15,\"distances\":[1,null],\"active\":true}"
+        );
+    }
+
+    #[test]
+    fn test_json_parse() {
+        let json = Json::parse(
+            " {\"name\": \"Q\\\"1\\u00e9\", \"capacity\": -1.5e1, \"packages\": [null, true, {}], \"x\": []} ",
+        )
+        .unwrap();
+        assert_eq!(json.get("name").and_then(Json::as_str), Some("Q\"1é"));
+        assert_eq!(json.get("capacity").and_then(Json::as_i32), Some(-15));
+        assert_eq!(
+            json.get("packages"),
+            Some(&Json::Array(vec![
+                Json::Null,
+                Json::Bool(true),
+                Json::Object(vec![])
+            ]))
+        );
+        assert_eq!(Json::parse(&json.to_string()), Ok(json));
+        assert!(Json::parse("{\"name\": }").is_err());
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("1 2").is_err());
+        assert_eq!(Json::Number(1.5).as_i32(), None);
+        assert_eq!(Json::Number(1e10).as_i32(), None);
+
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Json::parse(&nested(128)).is_ok());
+        assert!(Json::parse(&nested(129)).is_err());
+        assert!(Json::parse(&nested(100_000)).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use crate::{destination::Destination, graph::Graph, min_heap::MinHeap};
+
+impl Graph {
+    // Shortest distance of a direct edge between two stations, the shorter one
+    // when there are parallel edges.
+    pub fn get_edge_distance(&self, from: usize, to: usize) -> Option<i32> {
+        self.get_neighbours(from)
+            .filter(|(next, _)| *next == to)
+            .map(|(_, distance)| distance)
+            .min()
+    }
+    fn get_path_distance(&self, path: &[usize]) -> i32 {
+        path.windows(2)
+            .map(|leg| self.get_edge_distance(leg[0], leg[1]).unwrap_or(0))
+            .sum()
+    }
+    // Dijkstra from `from` to `to` that may not pass through `banned_stations`
+    // or use the edges in `banned_edges`. Returns the stations on the path.
+    fn get_restricted_path(
+        &self,
+        from: usize,
+        to: usize,
+        banned_stations: &HashSet<usize>,
+        banned_edges: &HashSet<(usize, usize)>,
+    ) -> Option<Vec<usize>> {
+        let mut distances = vec![i32::MAX; self.stations.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.stations.len()];
+        let mut min_heap: MinHeap<usize, i32> = MinHeap::new();
+
+        distances[from] = 0;
+        min_heap.push(from, 0);
+
+        while let Some((current, current_distance)) = min_heap.pop() {
+            if current == to {
+                break;
+            }
+            for (next, distance) in self.get_neighbours(current) {
+                if banned_stations.contains(&next) || banned_edges.contains(&(current, next)) {
+                    continue;
+                }
+                let new_distance = current_distance + distance;
+                if new_distance < distances[next]
+                    || (new_distance == distances[next]
+                        && self.is_better_tie(next, current, &|station| previous[station]))
+                {
+                    distances[next] = new_distance;
+                    previous[next] = Some(current);
+                    min_heap.push(next, new_distance);
+                }
+            }
+        }
+
+        if distances[to] == i32::MAX {
+            return None;
+        }
+        let mut path: Vec<usize> = vec![to];
+        let mut station = to;
+        while let Some(prev) = previous[station] {
+            path.push(prev);
+            station = prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+    // Up to `k` loopless paths from `from` to `to` in order of length, using
+    // Yen's algorithm.
+    pub fn k_shortest_paths(&mut self, from: String, to: String, k: usize) -> Vec<Destination> {
+        let (from_id, to_id) = match (self.get_station_id(&from), self.get_station_id(&to)) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            _ => return vec![],
+        };
+        self.build_adjacency();
+
+        let mut paths: Vec<Vec<usize>> = vec![];
+        let mut candidates: MinHeap<Vec<usize>, i32> = MinHeap::new();
+        if k > 0 {
+            if let Some(path) =
+                self.get_restricted_path(from_id, to_id, &HashSet::new(), &HashSet::new())
+            {
+                paths.push(path);
+            }
+        }
+
+        while !paths.is_empty() && paths.len() < k {
+            let previous_path = paths[paths.len() - 1].clone();
+            for i in 0..previous_path.len() - 1 {
+                let spur_station = previous_path[i];
+                let root_path = &previous_path[..=i];
+
+                let mut banned_edges: HashSet<(usize, usize)> = HashSet::new();
+                for path in paths.iter() {
+                    if path.len() > i + 1 && &path[..=i] == root_path {
+                        banned_edges.insert((path[i], path[i + 1]));
+                        banned_edges.insert((path[i + 1], path[i]));
+                    }
+                }
+                let banned_stations: HashSet<usize> = root_path[..i].iter().copied().collect();
+
+                if let Some(spur_path) =
+                    self.get_restricted_path(spur_station, to_id, &banned_stations, &banned_edges)
+                {
+                    let mut candidate = root_path[..i].to_vec();
+                    candidate.extend(spur_path);
+                    if !paths.contains(&candidate) {
+                        let distance = self.get_path_distance(&candidate);
+                        candidates.push(candidate, distance);
+                    }
+                }
+            }
+
+            match candidates.pop() {
+                Some((path, _)) => paths.push(path),
+                None => break,
+            }
+        }
+
+        let mut destinations: Vec<Destination> = vec![];
+        for path in paths {
+            let mut legs: Vec<(usize, i32, i32)> = vec![];
+            for leg in path.windows(2) {
+                legs.push((
+                    leg[1],
+                    self.get_edge_distance(leg[0], leg[1]).unwrap_or(0),
+                    0,
+                ));
+            }
+            destinations.push(self.build_destination(from_id, to_id, legs));
+        }
+        destinations
+    }
+    // Whether the path can be driven from `departure_time` without waiting
+    // for any closure.
+    pub fn is_path_open(&self, destination: &Destination, departure_time: i32) -> bool {
+        let mut stations: Vec<String> = vec![destination.from.clone()];
+        let mut distances: Vec<i32> = vec![];
+        for checkpoint in destination.checkpoints.iter() {
+            stations.push(checkpoint.to.clone());
+            distances.push(checkpoint.distance);
+        }
+        stations.push(destination.to.clone());
+        distances.push(destination.distance);
+
+        let mut time = departure_time;
+        for (i, distance) in distances.into_iter().enumerate() {
+            let (from, to) = match (
+                self.get_station_id(&stations[i]),
+                self.get_station_id(&stations[i + 1]),
+            ) {
+                (Some(from), Some(to)) => (from, to),
+                _ => return false,
+            };
+            if self.get_departure_time(from, to, distance, time) != time {
+                return false;
+            }
+            time += distance;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{graph::Graph, input::Input, movement::Movement, navigation::Navigation};
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 40);
+        graph.add_edge("A".to_string(), "C".to_string(), 10);
+        graph.add_edge("B".to_string(), "C".to_string(), 20);
+        graph.add_edge("B".to_string(), "D".to_string(), 10);
+        graph.add_edge("C".to_string(), "D".to_string(), 50);
+        let destinations = graph.k_shortest_paths("A".to_string(), "D".to_string(), 5);
+        let paths: Vec<(i32, Vec<String>)> = destinations
+            .into_iter()
+            .map(|destination| {
+                (
+                    destination.cumulative_distance,
+                    destination
+                        .checkpoints
+                        .into_iter()
+                        .map(|checkpoint| checkpoint.to)
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (40, vec!["C".to_string(), "B".to_string()]),
+                (50, vec!["B".to_string()]),
+                (60, vec!["C".to_string()]),
+                (110, vec!["B".to_string(), "C".to_string()]),
+            ]
+        );
+    }
+
+    // One package from A to C, with the short way through B closed until
+    // `reopens_at`.
+    fn create_input(reopens_at: i32) -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "A".to_string(), "D".to_string(), 15),
+                ("E4".to_string(), "D".to_string(), "C".to_string(), 15),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            closures: vec![("E2".to_string(), 5, reopens_at)],
+            ..Default::default()
+        }
+    }
+
+    fn calculate(reopens_at: i32, route_alternatives: usize) -> Vec<Movement> {
+        let mut navigation = Navigation::new(create_input(reopens_at));
+        navigation.route_alternatives = route_alternatives;
+        navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_navigation_route_alternatives() {
+        // Waiting at B for E2 gets there at 25, sooner than going round by D.
+        let movements = calculate(15, 0);
+        assert_eq!(movements[0].to, "B");
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 25);
+        let movements = calculate(15, 2);
+        assert_eq!(movements[0].to, "B");
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 25);
+
+        // Waiting until 20 gets there at 30 as well, so the train goes round
+        // without stopping.
+        let movements = calculate(20, 0);
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 30);
+        let movements = calculate(20, 2);
+        assert_eq!(movements[0].to, "D");
+        assert!(movements
+            .windows(2)
+            .all(|legs| legs[1].start_time == legs[0].end_time));
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 30);
+    }
+}
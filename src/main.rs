@@ -1,19 +1,52 @@
+use std::env;
+use std::net::TcpListener;
 use std::time::Instant;
 
 use crate::input::Input;
 use crate::navigation::Navigation;
+use crate::server::Server;
 
+pub mod a_star;
+pub mod analysis;
+pub mod cost;
+pub mod decomposition;
 pub mod destination;
+pub mod disruption;
+pub mod distance_matrix;
+pub mod explanation;
+pub mod geometry;
 pub mod graph;
 pub mod input;
+pub mod json;
+pub mod k_shortest_paths;
 pub mod min_heap;
 pub mod movement;
+pub mod mutation;
 pub mod navigation;
 pub mod package;
 pub mod route;
+pub mod server;
+pub mod shift;
+pub mod snapshot;
+pub mod stats;
+pub mod tie_break;
+pub mod trace;
 pub mod train;
+pub mod validation;
 
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(|arg| arg.as_str()) == Some("serve") {
+        let port = args.get(2).map(|port| port.as_str()).unwrap_or("8080");
+        let listener = match TcpListener::bind(format!("127.0.0.1:{}", port)) {
+            Ok(listener) => listener,
+            Err(error) => panic!("Could not bind to port {}: {}", port, error),
+        };
+        println!("Listening on http://127.0.0.1:{}", port);
+        Server::new().serve(listener);
+        return;
+    }
+
     let start = Instant::now();
     let mut navigation = Navigation::new(Input {
         edges: vec![
@@ -22,8 +55,9 @@ fn main() {
         ],
         packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
         trains: vec![("Q1".to_string(), 6, "B".to_string())],
+        ..Default::default()
     });
-    let movements = navigation.calculate(
+    let movements = navigation.solve(
         navigation.trains.clone(),
         navigation.packages.clone(),
         vec![],
@@ -31,6 +65,8 @@ fn main() {
     let duration = start.elapsed();
 
     println!("Movements: {:#?}", movements);
+    println!("Summary: {:#?}", navigation.summarise(movements));
+    println!("Stats: {:#?}", navigation.stats);
     println!("Duration: {:?}", duration);
     println!("Run `cargo t` to test all cases");
 }
@@ -1,132 +1,189 @@
-use crate::route::Route;
+use std::{collections::HashMap, hash::Hash};
 
-pub struct MinHeap {
-    pub heap: Vec<Route>,
+// Indexed binary heap keyed by `K`. Each key is held at most once, so a
+// lower priority for a queued key is a decrease-key rather than a duplicate.
+// Entries with equal priorities come out in the order they were first added.
+pub struct MinHeap<K, P> {
+    heap: Vec<(K, P, u64)>,
+    positions: HashMap<K, usize>,
+    sequence: u64,
 }
 
-impl MinHeap {
-    pub fn new() -> MinHeap {
-        MinHeap { heap: [].to_vec() }
+impl<K: Clone + Eq + Hash, P: Ord> Default for MinHeap<K, P> {
+    fn default() -> Self {
+        Self::new()
     }
-    pub fn get_left_child_index(parent_index: i32) -> i32 {
+}
+
+impl<K: Clone + Eq + Hash, P: Ord> MinHeap<K, P> {
+    pub fn new() -> MinHeap<K, P> {
+        MinHeap {
+            heap: vec![],
+            positions: HashMap::new(),
+            sequence: 0,
+        }
+    }
+    fn get_left_child_index(parent_index: usize) -> usize {
         2 * parent_index + 1
     }
-    pub fn get_right_child_index(parent_index: i32) -> i32 {
+    fn get_right_child_index(parent_index: usize) -> usize {
         2 * parent_index + 2
     }
-    pub fn get_parent_index(child_index: i32) -> i32 {
-        let mid: f32 = (child_index as f32 - 1.0) / 2.0;
-        mid.floor() as i32
-    }
-    pub fn has_left_child(&mut self, index: i32) -> bool {
-        MinHeap::get_left_child_index(index) < self.heap.len() as i32
+    fn get_parent_index(child_index: usize) -> usize {
+        (child_index - 1) / 2
     }
-    pub fn has_right_child(&mut self, index: i32) -> bool {
-        MinHeap::get_right_child_index(index) < self.heap.len() as i32
+    fn is_less(&self, index_one: usize, index_two: usize) -> bool {
+        let (_, priority_one, sequence_one) = &self.heap[index_one];
+        let (_, priority_two, sequence_two) = &self.heap[index_two];
+        (priority_one, sequence_one) < (priority_two, sequence_two)
     }
-    pub fn has_parent(index: i32) -> bool {
-        MinHeap::get_parent_index(index) >= 0
+    fn swap(&mut self, index_one: usize, index_two: usize) {
+        self.heap.swap(index_one, index_two);
+        self.positions
+            .insert(self.heap[index_one].0.clone(), index_one);
+        self.positions
+            .insert(self.heap[index_two].0.clone(), index_two);
     }
-    pub fn left_child(&mut self, index: i32) -> Route {
-        self.heap[MinHeap::get_left_child_index(index) as usize].clone()
+    pub fn len(&self) -> usize {
+        self.heap.len()
     }
-    pub fn right_child(&mut self, index: i32) -> Route {
-        self.heap[MinHeap::get_right_child_index(index) as usize].clone()
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
     }
-    pub fn parent(&mut self, index: i32) -> Route {
-        self.heap[MinHeap::get_parent_index(index) as usize].clone()
+    pub fn contains(&self, key: &K) -> bool {
+        self.positions.contains_key(key)
     }
-    pub fn swap(&mut self, index_one: i32, index_two: i32) {
-        let temp = self.heap[index_one as usize].clone();
-        self.heap[index_one as usize] = self.heap[index_two as usize].clone();
-        self.heap[index_two as usize] = temp;
+    pub fn priority(&self, key: &K) -> Option<&P> {
+        self.positions.get(key).map(|index| &self.heap[*index].1)
     }
-    pub fn peek(&mut self) -> Option<Route> {
-        if self.heap.len() != 0 {
-            return Some(self.heap[0].clone());
-        }
-        None
+    pub fn peek(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|(key, priority, _)| (key, priority))
     }
-    pub fn remove(&mut self) -> Option<Route> {
-        if self.heap.len() == 0 {
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        if self.heap.is_empty() {
             return None;
         }
-        let route: Option<Route> = Some(self.heap[0].clone());
-        self.heap[0] = self.heap[self.heap.len() - 1].clone();
-        self.heap.pop();
-        self.heapify_down();
-        return route;
-    }
-    pub fn add(&mut self, route: Route) {
-        self.heap.push(route);
-        self.heapify_up();
-    }
-    pub fn heapify_up(&mut self) {
-        let mut index: i32 = self.heap.len() as i32 - 1;
-        while MinHeap::has_parent(index)
-            && MinHeap::parent(self, index).distance > self.heap[index as usize].distance
-        {
-            MinHeap::swap(self, MinHeap::get_parent_index(index), index);
-            index = MinHeap::get_parent_index(index);
+        let last_index = self.heap.len() - 1;
+        self.swap(0, last_index);
+        let (key, priority, _) = self.heap.pop()?;
+        self.positions.remove(&key);
+        self.heapify_down(0);
+        Some((key, priority))
+    }
+    // Adds the key, or lowers its priority if it is already queued with a
+    // higher one. Returns whether the heap changed.
+    pub fn push(&mut self, key: K, priority: P) -> bool {
+        if self.contains(&key) {
+            return self.decrease_key(&key, priority);
+        }
+        self.heap.push((key.clone(), priority, self.sequence));
+        self.sequence += 1;
+        let index = self.heap.len() - 1;
+        self.positions.insert(key, index);
+        self.heapify_up(index);
+        true
+    }
+    pub fn decrease_key(&mut self, key: &K, priority: P) -> bool {
+        let index = match self.positions.get(key) {
+            Some(index) => *index,
+            None => return false,
+        };
+        if priority >= self.heap[index].1 {
+            return false;
+        }
+        self.heap[index].1 = priority;
+        self.heapify_up(index);
+        true
+    }
+    // Visits the queued entries in heap order, not priority order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &P)> {
+        self.heap.iter().map(|(key, priority, _)| (key, priority))
+    }
+    fn heapify_up(&mut self, index: usize) {
+        let mut index = index;
+        while index > 0 && self.is_less(index, MinHeap::<K, P>::get_parent_index(index)) {
+            let parent_index = MinHeap::<K, P>::get_parent_index(index);
+            self.swap(parent_index, index);
+            index = parent_index;
         }
     }
-    pub fn heapify_down(&mut self) {
-        let mut index = 0;
-        while MinHeap::has_left_child(self, index) {
-            let mut smaller_child_index = MinHeap::get_left_child_index(index);
-            if MinHeap::has_right_child(self, index)
-                && MinHeap::right_child(self, index).distance
-                    < MinHeap::left_child(self, index).distance
-            {
-                smaller_child_index = MinHeap::get_right_child_index(index);
+    fn heapify_down(&mut self, index: usize) {
+        let mut index = index;
+        loop {
+            let left_child_index = MinHeap::<K, P>::get_left_child_index(index);
+            let right_child_index = MinHeap::<K, P>::get_right_child_index(index);
+            if left_child_index >= self.heap.len() {
+                break;
             }
-            if self.heap[index as usize].distance < self.heap[smaller_child_index as usize].distance
+            let mut smaller_child_index = left_child_index;
+            if right_child_index < self.heap.len()
+                && self.is_less(right_child_index, left_child_index)
             {
+                smaller_child_index = right_child_index;
+            }
+            if self.is_less(index, smaller_child_index) {
                 break;
-            } else {
-                MinHeap::swap(self, index, smaller_child_index);
             }
+            self.swap(index, smaller_child_index);
             index = smaller_child_index;
         }
     }
 }
 
-pub fn create_min_heap() -> MinHeap {
-    MinHeap { heap: [].to_vec() }
+pub fn create_min_heap<K: Clone + Eq + Hash, P: Ord>() -> MinHeap<K, P> {
+    MinHeap::new()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{min_heap::MinHeap, route::Route};
+    use crate::min_heap::MinHeap;
 
     #[test]
     fn test_min_heap() {
-        let mut min_heap = MinHeap::new();
-        min_heap.add(Route {
-            to: "a".to_string(),
-            distance: 1,
-        });
-        min_heap.add(Route {
-            to: "b".to_string(),
-            distance: 5,
-        });
-        min_heap.add(Route {
-            to: "c".to_string(),
-            distance: 2,
-        });
-        let res1 = min_heap.remove();
-        assert!(res1.is_some_and(|route| route.to == "a"));
-        let res2 = min_heap.remove();
-        assert!(res2.is_some_and(|route| route.to == "c"));
-        min_heap.add(Route {
-            to: "d".to_string(),
-            distance: 3,
-        });
-        let res3 = min_heap.remove();
-        assert!(res3.is_some_and(|route| route.to == "d"));
-        let res4 = min_heap.remove();
-        assert!(res4.is_some_and(|route| route.to == "b"));
-        let res5 = min_heap.remove();
+        let mut min_heap: MinHeap<String, i32> = MinHeap::new();
+        min_heap.push("a".to_string(), 1);
+        min_heap.push("b".to_string(), 5);
+        min_heap.push("c".to_string(), 2);
+        let res1 = min_heap.pop();
+        assert!(res1.is_some_and(|(key, _)| key == "a"));
+        let res2 = min_heap.pop();
+        assert!(res2.is_some_and(|(key, _)| key == "c"));
+        min_heap.push("d".to_string(), 3);
+        let res3 = min_heap.pop();
+        assert!(res3.is_some_and(|(key, _)| key == "d"));
+        let res4 = min_heap.pop();
+        assert!(res4.is_some_and(|(key, _)| key == "b"));
+        let res5 = min_heap.pop();
         assert!(res5.is_none());
+        assert!(min_heap.is_empty());
+    }
+
+    #[test]
+    fn test_min_heap_decrease_key() {
+        let mut min_heap: MinHeap<String, i32> = MinHeap::new();
+        min_heap.push("a".to_string(), 10);
+        min_heap.push("b".to_string(), 20);
+        assert!(min_heap.push("b".to_string(), 5));
+        assert!(!min_heap.push("a".to_string(), 15));
+        assert!(!min_heap.decrease_key(&"x".to_string(), 1));
+        assert_eq!(min_heap.len(), 2);
+        assert_eq!(min_heap.priority(&"a".to_string()), Some(&10));
+        assert_eq!(min_heap.peek(), Some((&"b".to_string(), &5)));
+        assert_eq!(min_heap.iter().count(), 2);
+        assert_eq!(min_heap.pop(), Some(("b".to_string(), 5)));
+        assert_eq!(min_heap.pop(), Some(("a".to_string(), 10)));
+    }
+
+    #[test]
+    fn test_min_heap_stable_ties() {
+        let mut min_heap: MinHeap<String, i32> = MinHeap::new();
+        for key in ["d", "b", "e", "a", "c"] {
+            min_heap.push(key.to_string(), 1);
+        }
+        let mut keys: Vec<String> = vec![];
+        while let Some((key, _)) = min_heap.pop() {
+            keys.push(key);
+        }
+        assert_eq!(keys, vec!["d", "b", "e", "a", "c"]);
     }
 }
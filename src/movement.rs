@@ -1,3 +1,5 @@
+use crate::json::Json;
+
 #[derive(Clone, PartialEq, Debug)]
 
 pub struct Movement {
@@ -5,7 +7,77 @@ pub struct Movement {
     pub end_time: i32,
     pub from: String,
     pub to: String,
+    // The edge the train went over, "" for breaks.
+    pub edge: String,
     pub train: String,
     pub packages_picked_up: Vec<String>,
     pub packages_delivered: Vec<String>,
+    // Packages left at a hub at the end of the movement for another train.
+    pub packages_transferred: Vec<String>,
+}
+
+impl Movement {
+    pub fn from_json(json: &Json) -> Result<Movement, String> {
+        let get_string = |key: &str| match json.get(key).and_then(Json::as_str) {
+            Some(value) => Ok(value.to_string()),
+            None => Err(format!("Movement {} missing", key)),
+        };
+        let get_number = |key: &str| match json.get(key).and_then(Json::as_i32) {
+            Some(value) => Ok(value),
+            None => Err(format!("Movement {} missing", key)),
+        };
+        let get_packages = |key: &str| {
+            let mut packages: Vec<String> = vec![];
+            if let Some(values) = json.get(key).and_then(Json::as_array) {
+                for value in values {
+                    match value.as_str() {
+                        Some(package) => packages.push(package.to_string()),
+                        None => return Err(format!("Movement {} must be names", key)),
+                    }
+                }
+            }
+            Ok(packages)
+        };
+        Ok(Movement {
+            start_time: get_number("start_time")?,
+            end_time: get_number("end_time")?,
+            from: get_string("from")?,
+            to: get_string("to")?,
+            // Plans written before edges were recorded have none.
+            edge: get_string("edge").unwrap_or_default(),
+            train: get_string("train")?,
+            packages_picked_up: get_packages("packages_picked_up")?,
+            packages_delivered: get_packages("packages_delivered")?,
+            packages_transferred: get_packages("packages_transferred")?,
+        })
+    }
+}
+
+impl From<Movement> for Json {
+    fn from(movement: Movement) -> Json {
+        let mut entries = vec![
+            ("start_time".to_string(), Json::from(movement.start_time)),
+            ("end_time".to_string(), Json::from(movement.end_time)),
+            ("from".to_string(), Json::from(movement.from)),
+            ("to".to_string(), Json::from(movement.to)),
+            ("edge".to_string(), Json::from(movement.edge)),
+            ("train".to_string(), Json::from(movement.train)),
+            (
+                "packages_picked_up".to_string(),
+                Json::from(movement.packages_picked_up),
+            ),
+            (
+                "packages_delivered".to_string(),
+                Json::from(movement.packages_delivered),
+            ),
+        ];
+        // Only plans with hubs have transfers, the rest keep their shape.
+        if !movement.packages_transferred.is_empty() {
+            entries.push((
+                "packages_transferred".to_string(),
+                Json::from(movement.packages_transferred),
+            ));
+        }
+        Json::Object(entries)
+    }
 }
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+
+use crate::{graph::Graph, navigation::Navigation};
+
+impl Graph {
+    // Drops the cached shortest path trees that a change to the edges between
+    // `from` and `to` could make wrong: those using one of the edges when an
+    // existing edge changed, and those the new distance would shorten or tie.
+    pub fn invalidate_edge(
+        &mut self,
+        from: usize,
+        to: usize,
+        changed: bool,
+        new_distance: Option<i32>,
+    ) {
+        self.cache.retain(|_, shortest_paths| {
+            let uses_edge = shortest_paths.previous[to] == Some(from)
+                || shortest_paths.previous[from] == Some(to);
+            if changed && uses_edge {
+                return false;
+            }
+            if let Some(new_distance) = new_distance {
+                let (from_distance, to_distance) =
+                    (shortest_paths.distances[from], shortest_paths.distances[to]);
+                if (from_distance != i32::MAX && from_distance + new_distance <= to_distance)
+                    || (to_distance != i32::MAX && to_distance + new_distance <= from_distance)
+                {
+                    return false;
+                }
+            }
+            true
+        });
+    }
+    fn get_station_id_or_panic(&self, name: &str) -> usize {
+        match self.get_station_id(name) {
+            Some(id) => id,
+            None => panic!("Station not found"),
+        }
+    }
+    fn is_edge_between(edge: &(usize, usize, i32, String), from: usize, to: usize) -> bool {
+        (edge.0 == from && edge.1 == to) || (edge.0 == to && edge.1 == from)
+    }
+    // Removes every edge between the two stations. Their closures are kept in
+    // case the edge is added back.
+    pub fn remove_edge(&mut self, from: String, to: String) {
+        let (from_id, to_id) = (
+            self.get_station_id_or_panic(&from),
+            self.get_station_id_or_panic(&to),
+        );
+        let edges_count = self.edges.len();
+        self.edges
+            .retain(|edge| !Graph::is_edge_between(edge, from_id, to_id));
+        if self.edges.len() == edges_count {
+            panic!("Edge not found");
+        }
+        self.invalidate_adjacency();
+        self.invalidate_edge(from_id, to_id, true, None);
+    }
+    // Sets the distance of every edge between the two stations.
+    pub fn update_distance(&mut self, from: String, to: String, distance: i32) {
+        let (from_id, to_id) = (
+            self.get_station_id_or_panic(&from),
+            self.get_station_id_or_panic(&to),
+        );
+        let mut found = false;
+        for edge in self.edges.iter_mut() {
+            if Graph::is_edge_between(edge, from_id, to_id) {
+                edge.2 = distance;
+                found = true;
+            }
+        }
+        if !found {
+            panic!("Edge not found");
+        }
+        self.invalidate_adjacency();
+        self.invalidate_edge(from_id, to_id, true, Some(distance));
+        self.check_admissible(from_id, to_id, distance);
+    }
+    // Removes the station with its edges, closures and coordinates. Its ID is
+    // not reused, the name is only forgotten so it can be added again as a new
+    // station.
+    pub fn remove_station(&mut self, name: String) {
+        let id = self.get_station_id_or_panic(&name);
+        let mut neighbours: Vec<usize> = vec![];
+        for (from, to, _, _) in self.edges.iter() {
+            if *from == id {
+                neighbours.push(*to);
+            } else if *to == id {
+                neighbours.push(*from);
+            }
+        }
+        self.edges
+            .retain(|(from, to, _, _)| *from != id && *to != id);
+        self.invalidate_adjacency();
+        for neighbour in neighbours {
+            self.invalidate_edge(id, neighbour, true, None);
+        }
+
+        self.cache.remove(&id);
+        self.closures
+            .retain(|(from, to), _| *from != id && *to != id);
+        self.coordinates.remove(&id);
+        self.station_ids.remove(&name);
+    }
+    pub fn is_removed(&self, id: usize) -> bool {
+        self.station_ids.get(&self.stations[id]) != Some(&id)
+    }
+    // Moves the edges, closures and, when `into` has none, the coordinates of
+    // `from` onto `into` and removes `from`. Edges between the two are dropped.
+    pub fn merge_stations(&mut self, from: String, into: String) {
+        let from_id = self.get_station_id_or_panic(&from);
+        if from == into {
+            return;
+        }
+        let into_id = self.add_station(into.clone());
+
+        let mut moved_edges: Vec<(usize, i32, String)> = vec![];
+        for (edge_from, edge_to, distance, name) in self.edges.iter() {
+            if *edge_from == from_id && *edge_to != into_id {
+                moved_edges.push((*edge_to, *distance, name.clone()));
+            } else if *edge_to == from_id && *edge_from != into_id {
+                moved_edges.push((*edge_from, *distance, name.clone()));
+            }
+        }
+        for (station, distance, name) in moved_edges {
+            self.add_named_edge(name, into.clone(), self.stations[station].clone(), distance);
+        }
+
+        let closure_keys: Vec<(usize, usize)> = self.closures.keys().copied().collect();
+        for (closure_from, closure_to) in closure_keys {
+            let key = if closure_from == from_id && closure_to != into_id {
+                (into_id, closure_to)
+            } else if closure_to == from_id && closure_from != into_id {
+                (closure_from, into_id)
+            } else {
+                continue;
+            };
+            let windows = self.closures[&(closure_from, closure_to)].clone();
+            let merged_windows = self.closures.entry(key).or_default();
+            merged_windows.extend(windows);
+            merged_windows.sort();
+            merged_windows.dedup();
+        }
+
+        if let Some(coordinates) = self.coordinates.get(&from_id).copied() {
+            self.coordinates.entry(into_id).or_insert(coordinates);
+            self.check_station_admissible(into_id);
+        }
+        self.remove_station(from);
+    }
+    // Renames the station, merging it into `to` when that already exists.
+    // Station names settle ties between paths, so the cached trees are
+    // dropped.
+    pub fn rename_station(&mut self, from: String, to: String) {
+        if self.get_station_id(&to).is_some() {
+            self.merge_stations(from, to);
+            return;
+        }
+        let id = self.get_station_id_or_panic(&from);
+        self.station_ids.remove(&from);
+        self.station_ids.insert(to.clone(), id);
+        self.stations[id] = to;
+        self.cache.clear();
+    }
+}
+
+impl Navigation {
+    fn refresh_edges(&mut self, from: String, to: String) {
+        self.graph.remove_edge(from.clone(), to.clone());
+        let mut edges: Vec<(String, i32)> = vec![];
+        for (name, (edge_from, edge_to, distance)) in self.edges.iter() {
+            if (*edge_from == from && *edge_to == to) || (*edge_from == to && *edge_to == from) {
+                edges.push((name.clone(), *distance));
+            }
+        }
+        // Sorted so parallel edges of the same distance keep a stable order.
+        edges.sort();
+        for (name, distance) in edges {
+            self.graph
+                .add_named_edge(name, from.clone(), to.clone(), distance);
+        }
+
+        self.cache = HashMap::new();
+        self.refresh_distance_matrix();
+    }
+    pub fn remove_edge(&mut self, name: String) {
+        let (from, to, _) = match self.edges.remove(&name) {
+            Some(edge) => edge,
+            None => panic!("Edge not found"),
+        };
+        self.refresh_edges(from, to);
+    }
+    pub fn update_edge_distance(&mut self, name: String, distance: i32) {
+        let (from, to) = match self.edges.get_mut(&name) {
+            Some(edge) => {
+                edge.2 = distance;
+                (edge.0.clone(), edge.1.clone())
+            }
+            None => panic!("Edge not found"),
+        };
+        self.refresh_edges(from, to);
+    }
+    fn refresh_stations(&mut self) {
+        self.cache = HashMap::new();
+        self.refresh_distance_matrix();
+    }
+    // Points the trains, packages and hubs at `from` to `to` instead.
+    fn move_station_references(&mut self, from: &str, to: &str) {
+        let rename = |station: &mut String| {
+            if station == from {
+                *station = to.to_string();
+            }
+        };
+        for train in self.trains.values_mut() {
+            rename(&mut train.start);
+            rename(&mut train.end);
+            rename(&mut train.current_location);
+        }
+        for package in self.packages.values_mut() {
+            rename(&mut package.from);
+            rename(&mut package.to);
+            rename(&mut package.location);
+            for (_, hub) in package.legs.iter_mut() {
+                rename(hub);
+            }
+        }
+        let mut hubs: Vec<String> = vec![];
+        for mut hub in self.hubs.drain(..) {
+            rename(&mut hub);
+            if !hubs.contains(&hub) {
+                hubs.push(hub);
+            }
+        }
+        self.hubs = hubs;
+    }
+    // Removes the station and its edges. Trains, packages and hubs cannot
+    // be left without it, so it must not be one of theirs.
+    pub fn remove_station(&mut self, name: String) {
+        let in_use =
+            Navigation::get_interesting_stations(self.trains.clone(), self.packages.clone())
+                .contains(&name)
+                || self.hubs.contains(&name);
+        if in_use {
+            panic!("Station in use");
+        }
+        self.graph.remove_station(name.clone());
+        self.edges
+            .retain(|_, (from, to, _)| *from != name && *to != name);
+        self.refresh_stations();
+    }
+    // Moves the edges of `from` onto `into`, dropping those between the two,
+    // along with the trains, packages and hubs at it.
+    pub fn merge_stations(&mut self, from: String, into: String) {
+        self.graph.merge_stations(from.clone(), into.clone());
+        if from == into {
+            return;
+        }
+        self.edges.retain(|_, (edge_from, edge_to, _)| {
+            !((*edge_from == from && *edge_to == into) || (*edge_from == into && *edge_to == from))
+        });
+        for (edge_from, edge_to, _) in self.edges.values_mut() {
+            for station in [edge_from, edge_to] {
+                if *station == from {
+                    *station = into.clone();
+                }
+            }
+        }
+        self.move_station_references(&from, &into);
+        self.refresh_stations();
+    }
+    // Renames the station, merging it into `to` when that already exists.
+    pub fn rename_station(&mut self, from: String, to: String) {
+        if self.graph.get_station_id(&to).is_some() {
+            self.merge_stations(from, to);
+            return;
+        }
+        self.graph.rename_station(from.clone(), to.clone());
+        for (edge_from, edge_to, _) in self.edges.values_mut() {
+            for station in [edge_from, edge_to] {
+                if *station == from {
+                    *station = to.clone();
+                }
+            }
+        }
+        self.move_station_references(&from, &to);
+        self.refresh_stations();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{graph::Graph, input::Input, navigation::Navigation};
+
+    fn create_graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_edge("A".to_string(), "B".to_string(), 10);
+        graph.add_edge("B".to_string(), "C".to_string(), 10);
+        graph.add_edge("A".to_string(), "D".to_string(), 15);
+        graph.add_edge("D".to_string(), "C".to_string(), 15);
+        graph.add_edge("X".to_string(), "Y".to_string(), 5);
+        graph
+    }
+
+    #[test]
+    fn test_graph_mutation_invalidates_affected_trees() {
+        let mut graph = create_graph();
+        let (a, x) = (
+            graph.get_station_id("A").unwrap(),
+            graph.get_station_id("X").unwrap(),
+        );
+        graph.shortest_paths(a);
+        graph.shortest_paths(x);
+
+        graph.update_distance("D".to_string(), "C".to_string(), 20);
+        assert!(graph.cache.contains_key(&a));
+        assert!(graph.cache.contains_key(&x));
+
+        graph.update_distance("B".to_string(), "C".to_string(), 30);
+        assert!(graph.adjacency_dirty);
+        assert!(!graph.cache.contains_key(&a));
+        assert!(graph.cache.contains_key(&x));
+        assert_eq!(
+            graph
+                .get_destination("A".to_string(), "C".to_string())
+                .cumulative_distance,
+            35
+        );
+        assert!(!graph.adjacency_dirty);
+
+        graph.remove_edge("A".to_string(), "D".to_string());
+        assert_eq!(
+            graph
+                .get_destination("A".to_string(), "C".to_string())
+                .cumulative_distance,
+            40
+        );
+        graph.shortest_paths(a);
+        graph.add_edge("X".to_string(), "Z".to_string(), 5);
+        assert!(graph.cache.contains_key(&a));
+        assert!(!graph.cache.contains_key(&x));
+        graph.add_edge("A".to_string(), "C".to_string(), 40);
+        assert!(!graph.cache.contains_key(&a));
+    }
+
+    #[test]
+    fn test_remove_and_merge_stations() {
+        let mut graph = create_graph();
+        graph.remove_station("B".to_string());
+        assert_eq!(graph.get_station_id("B"), None);
+        assert_eq!(
+            graph
+                .get_destination("A".to_string(), "C".to_string())
+                .cumulative_distance,
+            30
+        );
+        assert_eq!(
+            graph.get_components(),
+            vec![
+                vec!["A".to_string(), "C".to_string(), "D".to_string()],
+                vec!["X".to_string(), "Y".to_string()],
+            ]
+        );
+
+        graph.merge_stations("X".to_string(), "C".to_string());
+        assert_eq!(
+            graph
+                .get_destination("A".to_string(), "Y".to_string())
+                .cumulative_distance,
+            35
+        );
+
+        graph.rename_station("Y".to_string(), "W".to_string());
+        let destination = graph.get_destination("A".to_string(), "W".to_string());
+        assert_eq!(destination.to, "W");
+        assert_eq!(destination.cumulative_distance, 35);
+    }
+
+    #[test]
+    fn test_navigation_update_edge_distance() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "A".to_string(), "C".to_string(), 30),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            ..Default::default()
+        });
+        navigation.update_edge_distance("E3".to_string(), 15);
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 15);
+
+        navigation.remove_edge("E3".to_string());
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 20);
+    }
+
+    fn calculate(navigation: &mut Navigation) -> Vec<(String, String, i32)> {
+        navigation
+            .calculate(
+                navigation.trains.clone(),
+                navigation.packages.clone(),
+                vec![],
+            )
+            .into_iter()
+            .map(|movement| (movement.from, movement.to, movement.end_time))
+            .collect()
+    }
+
+    #[test]
+    fn test_navigation_station_mutations() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "A".to_string(), "D".to_string(), 15),
+                ("E4".to_string(), "D".to_string(), "C".to_string(), 15),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            ..Default::default()
+        });
+        assert_eq!(calculate(&mut navigation).len(), 2);
+
+        navigation.remove_station("B".to_string());
+        assert!(!navigation.edges.contains_key("E1"));
+        assert!(!navigation.edges.contains_key("E2"));
+        assert_eq!(
+            calculate(&mut navigation),
+            vec![
+                ("A".to_string(), "D".to_string(), 15),
+                ("D".to_string(), "C".to_string(), 30),
+            ]
+        );
+
+        navigation.rename_station("D".to_string(), "W".to_string());
+        assert_eq!(
+            navigation.edges["E3"],
+            ("A".to_string(), "W".to_string(), 15)
+        );
+        assert_eq!(
+            calculate(&mut navigation),
+            vec![
+                ("A".to_string(), "W".to_string(), 15),
+                ("W".to_string(), "C".to_string(), 30),
+            ]
+        );
+
+        // The train and the package move along with A.
+        navigation.merge_stations("A".to_string(), "W".to_string());
+        assert!(!navigation.edges.contains_key("E3"));
+        assert_eq!(navigation.trains["Q1"].current_location, "W");
+        assert_eq!(navigation.packages["K1"].from, "W");
+        assert_eq!(
+            calculate(&mut navigation),
+            vec![("W".to_string(), "C".to_string(), 15)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Station in use")]
+    fn test_navigation_remove_station_in_use() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![("E1".to_string(), "A".to_string(), "B".to_string(), 10)],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "B".to_string())],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            ..Default::default()
+        });
+        navigation.remove_station("B".to_string());
+    }
+}
@@ -1,72 +1,424 @@
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
-    i32,
 };
 
 use crate::{
-    destination::Destination, graph::Graph, input::Input, movement::Movement, package::Package,
+    cost::Objective,
+    destination::Destination,
+    distance_matrix::DistanceMatrix,
+    graph::Graph,
+    input::Input,
+    movement::Movement,
+    package::Package,
+    stats::{ProgressCallback, SolverStats},
+    trace::TraceEntry,
     train::Train,
 };
 
 pub struct Navigation {
     pub graph: Graph,
+    pub edges: HashMap<String, (String, String, i32)>,
     pub trains: HashMap<String, Train>,
     pub packages: HashMap<String, Package>,
-    pub cache: HashMap<String, Vec<Movement>>,
+    // The best plan from each state, None for states with no plan.
+    pub cache: HashMap<String, Option<Vec<Movement>>>,
+    pub distance_matrix: DistanceMatrix,
+    // How many alternatives to the shortest path to try instead of waiting
+    // for a closure to lift, when one arrives just as early. 0 to always wait.
+    pub route_alternatives: usize,
+    pub stats: SolverStats,
+    // Called with the stats every `progress_interval` states.
+    pub progress: Option<ProgressCallback>,
+    pub progress_interval: usize,
+    // How many `calculate` calls deep the search is, the deepest it went is in
+    // the stats.
+    depth: usize,
+    // Every job `calculate` tries, when set.
+    pub trace: Option<Vec<TraceEntry>>,
+    // Names of the capacity dimensions beyond weight.
+    pub dimensions: Vec<String>,
+    // Stations where packages can change trains.
+    pub hubs: Vec<String>,
+    // Toll for going over each edge, by edge name.
+    pub tolls: HashMap<String, i32>,
+    pub objective: Objective,
 }
 
 impl Navigation {
     pub fn new(input: Input) -> Navigation {
+        let mut navigation = Navigation::build(input);
+        navigation.refresh_distance_matrix();
+        navigation
+    }
+
+    // The network, trains and packages of the input, without the distance
+    // matrix worked out yet.
+    pub fn build(input: Input) -> Navigation {
         let mut graph = Graph::new();
-        let mut trains: HashMap<String, Train> = HashMap::new();
-        let mut packages: HashMap<String, Package> = HashMap::new();
+        let mut edges: HashMap<String, (String, String, i32)> = HashMap::new();
+        graph.tie_break = input.tie_break;
+
+        for (name, from, to, distance) in input.edges {
+            graph.add_named_edge(name.clone(), from.clone(), to.clone(), distance);
+            edges.insert(name, (from, to, distance));
+        }
+
+        for (name, start, end) in input.closures {
+            match edges.get(&name) {
+                Some((from, to, _)) => graph.add_closure(from.clone(), to.clone(), start, end),
+                None => panic!("Edge not found"),
+            }
+        }
 
-        for (_name, from, to, distance) in input.edges {
-            graph.add_edge(from, to, distance);
+        for (name, coordinates) in input.stations {
+            graph.set_coordinates(name, coordinates);
         }
 
+        Navigation::build_on(
+            graph,
+            edges,
+            Input {
+                edges: vec![],
+                closures: vec![],
+                stations: vec![],
+                tie_break: Default::default(),
+                ..input
+            },
+        )
+    }
+
+    // The trains, packages and options of the input on a network that is
+    // already built, without the distance matrix worked out yet. The edges,
+    // closures, stations and tie-break of the input are not read.
+    pub fn build_on(
+        graph: Graph,
+        edges: HashMap<String, (String, String, i32)>,
+        input: Input,
+    ) -> Navigation {
+        let mut trains: HashMap<String, Train> = HashMap::new();
         for (name, capacity, start) in input.trains {
             trains.insert(name.clone(), Train::new(name.clone(), capacity, start));
         }
 
+        let mut packages: HashMap<String, Package> = HashMap::new();
         for (name, weight, from, to) in input.packages {
             packages.insert(name.clone(), Package::new(name, weight, from, to));
         }
 
+        for (package_name, train_name) in input.must_assign {
+            if !trains.contains_key(&train_name) {
+                panic!("Train not found");
+            }
+            match packages.get_mut(&package_name) {
+                Some(package) if package.pinned_to.is_empty() => package.pinned_to = train_name,
+                Some(package) if package.pinned_to == train_name => {}
+                Some(_) => panic!("Package assigned to more than one train"),
+                None => panic!("Package not found"),
+            }
+        }
+        for (package_name, train_name) in input.must_not_assign {
+            if !trains.contains_key(&train_name) {
+                panic!("Train not found");
+            }
+            match packages.get_mut(&package_name) {
+                Some(package) => package.forbidden_trains.push(train_name),
+                None => panic!("Package not found"),
+            }
+        }
+
+        for (package_name, attribute) in input.package_attributes {
+            match packages.get_mut(&package_name) {
+                Some(package) => package.required_attributes.push(attribute),
+                None => panic!("Package not found"),
+            }
+        }
+        for (train_name, attribute) in input.train_attributes {
+            match trains.get_mut(&train_name) {
+                Some(train) => train.attributes.push(attribute),
+                None => panic!("Train not found"),
+            }
+        }
+        for (attribute_a, attribute_b) in input.incompatible_attributes {
+            for package in packages.values_mut() {
+                if package.required_attributes.contains(&attribute_a) {
+                    package.incompatible_attributes.push(attribute_b.clone());
+                }
+                if package.required_attributes.contains(&attribute_b) {
+                    package.incompatible_attributes.push(attribute_a.clone());
+                }
+            }
+        }
+
+        for (train_name, station) in input.train_ends {
+            match trains.get_mut(&train_name) {
+                Some(train) => train.end = station,
+                None => panic!("Train not found"),
+            }
+        }
+        if input.return_to_start {
+            for train in trains.values_mut() {
+                if train.end.is_empty() {
+                    train.end = train.start.clone();
+                }
+            }
+        }
+
+        for (train_name, fixed_cost, cost_per_distance) in input.train_costs {
+            match trains.get_mut(&train_name) {
+                Some(train) => {
+                    train.fixed_cost = fixed_cost;
+                    train.cost_per_distance = cost_per_distance;
+                }
+                None => panic!("Train not found"),
+            }
+        }
+        for (train_name, time) in input.train_departures {
+            match trains.get_mut(&train_name) {
+                Some(train) => train.available_at = time,
+                None => panic!("Train not found"),
+            }
+        }
+        for (train_name, max_operating_time) in input.max_operating_times {
+            match trains.get_mut(&train_name) {
+                Some(train) => train.max_operating_time = Some(max_operating_time),
+                None => panic!("Train not found"),
+            }
+        }
+        for (train_name, max_distance) in input.max_distances {
+            match trains.get_mut(&train_name) {
+                Some(train) => train.max_distance = Some(max_distance),
+                None => panic!("Train not found"),
+            }
+        }
+        for (train_name, rest_after, rest_duration) in input.rest_breaks {
+            match trains.get_mut(&train_name) {
+                Some(train) => {
+                    train.rest_after = Some(rest_after);
+                    train.rest_duration = rest_duration;
+                }
+                None => panic!("Train not found"),
+            }
+        }
+
+        let get_dimension =
+            |dimension: &String| match input.dimensions.iter().position(|name| name == dimension) {
+                Some(index) => index,
+                None => panic!("Dimension not found"),
+            };
+        for train in trains.values_mut() {
+            train.capacities = vec![i32::MAX; input.dimensions.len()];
+        }
+        for package in packages.values_mut() {
+            package.sizes = vec![0; input.dimensions.len()];
+        }
+        for (train_name, dimension, capacity) in input.train_capacities.iter() {
+            match trains.get_mut(train_name) {
+                Some(train) => train.capacities[get_dimension(dimension)] = *capacity,
+                None => panic!("Train not found"),
+            }
+        }
+        for (package_name, dimension, size) in input.package_sizes.iter() {
+            match packages.get_mut(package_name) {
+                Some(package) => package.sizes[get_dimension(dimension)] = *size,
+                None => panic!("Package not found"),
+            }
+        }
+
+        let mut navigation = Navigation::create(graph, edges, trains, packages);
+        navigation.dimensions = input.dimensions;
+        navigation.hubs = input.hubs;
+        navigation.objective = input.objective;
+        for (edge_name, toll) in input.tolls {
+            if !navigation.edges.contains_key(&edge_name) {
+                panic!("Edge not found");
+            }
+            navigation.tolls.insert(edge_name, toll);
+        }
+        navigation
+    }
+
+    fn create(
+        graph: Graph,
+        edges: HashMap<String, (String, String, i32)>,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+    ) -> Navigation {
         Navigation {
             graph,
+            edges,
             trains,
             packages,
             cache: HashMap::new(),
+            distance_matrix: DistanceMatrix::default(),
+            route_alternatives: 0,
+            stats: SolverStats::default(),
+            progress: None,
+            progress_interval: 1000,
+            depth: 0,
+            trace: None,
+            dimensions: [].to_vec(),
+            hubs: [].to_vec(),
+            tolls: HashMap::new(),
+            objective: Objective::default(),
+        }
+    }
+
+    // Plans on a graph that is already built, keeping its cached shortest
+    // paths.
+    pub fn from_graph(
+        graph: Graph,
+        edges: HashMap<String, (String, String, i32)>,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+    ) -> Navigation {
+        let mut navigation = Navigation::create(graph, edges, trains, packages);
+        navigation.refresh_distance_matrix();
+        navigation
+    }
+
+    // Takes the solver options of `other`, for a navigation made from parts of
+    // it with `from_graph`.
+    pub fn with_options_of(mut self, other: &Navigation) -> Navigation {
+        self.route_alternatives = other.route_alternatives;
+        self.dimensions = other.dimensions.clone();
+        self.tolls = other.tolls.clone();
+        self.objective = other.objective.clone();
+        // Hubs are interesting stations for the distance matrix.
+        if self.hubs != other.hubs {
+            self.hubs = other.hubs.clone();
+            self.refresh_distance_matrix();
+        }
+        self
+    }
+
+    pub fn refresh_distance_matrix(&mut self) {
+        let mut stations =
+            Navigation::get_interesting_stations(self.trains.clone(), self.packages.clone());
+        for hub in self.hubs.iter() {
+            if !stations.contains(hub) {
+                stations.push(hub.clone());
+            }
+        }
+        self.distance_matrix = self.graph.all_pairs(stations);
+    }
+
+    // Stations trains can be sent to or from: train starts and package
+    // origins and destinations.
+    pub fn get_interesting_stations(
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+    ) -> Vec<String> {
+        let mut stations: Vec<String> = vec![];
+        for (_, train) in trains {
+            stations.push(train.start);
+            stations.push(train.current_location);
+            if !train.end.is_empty() {
+                stations.push(train.end);
+            }
+        }
+        for (_, package) in packages {
+            stations.push(package.from);
+            stations.push(package.location);
+            stations.push(package.to);
+        }
+        stations.sort();
+        stations.dedup();
+        stations
+    }
+
+    pub fn get_destination_at(
+        &mut self,
+        from: String,
+        to: String,
+        departure_time: i32,
+    ) -> Destination {
+        if self.graph.closures.is_empty() {
+            if let Some(destination) = self.distance_matrix.get_destination(&from, &to) {
+                self.stats.dijkstra_cache_hits += 1;
+                return destination.clone();
+            }
+        }
+        self.stats.dijkstra_calls += 1;
+        let earliest = self
+            .graph
+            .get_destination_at(from.clone(), to.clone(), departure_time);
+        // An open alternative spares the train the wait, but only when it
+        // gets there no later.
+        if !earliest.waits.is_empty() && self.route_alternatives > 0 {
+            let arrival = earliest.cumulative_distance + earliest.waits.iter().sum::<i32>();
+            for destination in self
+                .graph
+                .k_shortest_paths(from, to, self.route_alternatives + 1)
+            {
+                if destination.cumulative_distance > arrival {
+                    break;
+                }
+                if self.graph.is_path_open(&destination, departure_time) {
+                    return destination;
+                }
+            }
         }
+        earliest
     }
 
     pub fn get_cache_key(
         trains: HashMap<String, Train>,
         packages: HashMap<String, Package>,
+        movements: &[Movement],
     ) -> String {
         let mut train_locations: Vec<(String, String)> = [].to_vec();
         for (train_name, train) in trains {
-            train_locations.push((train_name, train.current_location));
+            // With limits the rest of the plan depends on how far the train
+            // has come, and on when it left and got there, since waits count
+            // towards its operating time.
+            if train.has_limits() {
+                let times = match Navigation::get_train_times(&train_name, movements) {
+                    Some((first_departure, last_arrival)) => {
+                        format!("{}-{}", first_departure, last_arrival)
+                    }
+                    None => "-".to_string(),
+                };
+                train_locations.push((
+                    train_name,
+                    format!(
+                        "{}@{}/{}/{}",
+                        train.current_location,
+                        train.total_distance,
+                        train.driven_since_rest,
+                        times
+                    ),
+                ));
+            } else {
+                train_locations.push((train_name, train.current_location));
+            }
         }
         train_locations.sort_by(|(train_a, _), (train_b, _)| train_a.cmp(train_b));
 
         let mut packages_to_be_picked_up: Vec<(String, String)> = [].to_vec();
         let mut packages_picked_up: Vec<(String, String)> = [].to_vec();
         let mut packages_delivered: Vec<(String, String)> = [].to_vec();
+        let mut packages_transferred: Vec<(String, String)> = [].to_vec();
 
         for (package_name, pack) in packages {
-            if pack.delivered_by != "" {
+            if !pack.delivered_by.is_empty() {
                 packages_delivered.push((package_name.clone(), pack.delivered_by.clone()));
             }
-            if pack.picked_up_by != "" {
+            if !pack.picked_up_by.is_empty() {
                 packages_picked_up.push((package_name.clone(), pack.picked_up_by.clone()));
             }
-            if pack.to_be_picked_up_by != "" {
+            if !pack.to_be_picked_up_by.is_empty() {
                 packages_to_be_picked_up
                     .push((package_name.clone(), pack.to_be_picked_up_by.clone()));
             }
+            if !pack.legs.is_empty() {
+                let legs: Vec<String> = pack
+                    .legs
+                    .iter()
+                    .map(|(train, hub)| format!("{}@{}", train, hub))
+                    .collect();
+                packages_transferred.push((package_name.clone(), legs.join("/")));
+            }
         }
         packages_to_be_picked_up.sort_by(|(package_a, _), (package_b, _)| package_a.cmp(package_b));
         packages_picked_up.sort_by(|(package_a, _), (package_b, _)| package_a.cmp(package_b));
@@ -89,19 +441,28 @@ impl Navigation {
         let packages_delivered_cache_key = packages_delivered.into_iter().fold(
             "packages_delivered".to_string(),
             |acc, (package, train)| {
-                if acc == "" {
+                if acc.is_empty() {
                     return format!("{}:{}", package, train);
                 }
                 format!("{},{}:{}", acc, package, train)
             },
         );
-        format!(
+        let mut cache_key = format!(
             "{};{};{};{}",
             train_locations_cache_key,
             packages_to_be_picked_up_cache_key,
             packages_picked_up_cache_key,
             packages_delivered_cache_key
-        )
+        );
+        // Only plans with hubs have transfers, the rest keep their keys.
+        if !packages_transferred.is_empty() {
+            packages_transferred.sort();
+            cache_key = packages_transferred.into_iter().fold(
+                format!("{};packages_transferred", cache_key),
+                |acc, (package, legs)| format!("{},{}:{}", acc, package, legs),
+            );
+        }
+        cache_key
     }
     pub fn get_capable_trains(
         pack: Package,
@@ -110,22 +471,43 @@ impl Navigation {
     ) -> Vec<Train> {
         let mut capable_trains: Vec<Train> = [].to_vec();
         for (_, train) in trains {
+            // The train that left the package at a hub does not take it on.
+            if !pack.is_allowed_on(&train.name)
+                || !pack.is_supported_by(&train)
+                || pack
+                    .legs
+                    .last()
+                    .is_some_and(|(train_name, _)| *train_name == train.name)
+            {
+                continue;
+            }
             let mut train_packages = train.packages_to_pick_up.clone();
             for package_picked_up in train.packages_picked_up.clone() {
                 train_packages.push(package_picked_up);
             }
+            let mut compatible = true;
+            let mut load: Vec<i32> = vec![0; pack.sizes.len()];
             let packages_total_weight: i32 = train_packages.into_iter().fold(0, |acc, package| {
                 let weight = match packages.get(&package) {
-                    Some(item) => item.weight,
+                    Some(item) => {
+                        compatible = compatible && pack.is_compatible_with(item);
+                        for (dimension_load, size) in load.iter_mut().zip(item.sizes.iter()) {
+                            *dimension_load += size;
+                        }
+                        item.weight
+                    }
                     None => {
                         panic!("Package not found.");
                     }
                 };
                 acc + weight
             });
+            if !compatible {
+                continue;
+            }
             let train_capacity_left = train.capacity - packages_total_weight;
 
-            if train_capacity_left >= pack.weight {
+            if train_capacity_left >= pack.weight && pack.fits_next_to(&load, &train) {
                 capable_trains.push(train.clone());
             }
         }
@@ -167,6 +549,26 @@ impl Navigation {
         packages_to_deliver
     }
 
+    pub fn get_departure_time(
+        train: Train,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> i32 {
+        let mut departure_time = train.available_at;
+        for movement in movements {
+            if movement.train == train.name {
+                departure_time = departure_time.max(movement.end_time);
+            }
+        }
+        for package_name in train.packages_to_pick_up {
+            match packages.get(&package_name) {
+                Some(package) => departure_time = departure_time.max(package.available_at),
+                None => panic!("package not found"),
+            }
+        }
+        departure_time
+    }
+
     pub fn move_train(
         train: Train,
         destination: Destination,
@@ -174,21 +576,17 @@ impl Navigation {
         movements: Vec<Movement>,
     ) -> (Vec<Movement>, Vec<String>, Vec<String>) {
         let checkpoints = destination.checkpoints;
+        let waits = destination.waits;
         let mut new_movements = movements.clone();
-        let mut train_movements: Vec<Movement> = [].to_vec();
-        for movement in new_movements.clone() {
-            if movement.train == train.name {
-                train_movements.push(movement);
-            }
-        }
 
-        let mut start_time = 0;
-        if train_movements.len() > 0 {
-            start_time = train_movements[train_movements.len() - 1].end_time;
+        let mut start_time =
+            Navigation::get_departure_time(train.clone(), packages.clone(), movements.clone());
+        if !waits.is_empty() {
+            start_time += waits[0];
         }
 
         let mut end_time = start_time + destination.distance;
-        if checkpoints.len() > 0 {
+        if !checkpoints.is_empty() {
             end_time = start_time + checkpoints[0].distance;
         }
 
@@ -207,8 +605,10 @@ impl Navigation {
         }
 
         let mut to = destination.to.clone();
-        if checkpoints.len() > 0 {
+        let mut edge = destination.edge.clone();
+        if !checkpoints.is_empty() {
             to = checkpoints[0].to.clone();
+            edge = checkpoints[0].edge.clone();
         }
 
         let packages_to_deliver =
@@ -219,27 +619,33 @@ impl Navigation {
             end_time,
             from: destination.from.clone(),
             to: to.clone(),
+            edge,
             train: train.name.clone(),
             packages_picked_up: packages_picked_up.clone(),
             packages_delivered: packages_to_deliver.clone(),
+            packages_transferred: vec![],
         });
 
         for package in packages_to_deliver.clone() {
             packages_delivered.push(package);
         }
 
-        let mut i = 0;
         let checkpoints_len = checkpoints.len();
-        for checkpoint in checkpoints.clone() {
-            let start_time = end_time;
-            let mut end_time = start_time + destination.distance;
+        for (i, checkpoint) in checkpoints.clone().into_iter().enumerate() {
+            let mut start_time = end_time;
+            if i + 1 < waits.len() {
+                start_time += waits[i + 1];
+            }
+            end_time = start_time + destination.distance;
             if i < checkpoints_len - 1 {
                 end_time = start_time + checkpoints[i + 1].distance;
             }
 
             let mut to = destination.to.clone();
+            let mut edge = destination.edge.clone();
             if i < checkpoints_len - 1 {
                 to = checkpoints[i + 1].to.clone();
+                edge = checkpoints[i + 1].edge.clone();
             }
 
             let packages_to_deliver =
@@ -251,20 +657,56 @@ impl Navigation {
                 train: train.name.clone(),
                 from: checkpoint.to.clone(),
                 to: to.clone(),
+                edge,
                 packages_picked_up: vec![],
                 packages_delivered: packages_to_deliver.clone(),
+                packages_transferred: vec![],
             });
             for package in packages_to_deliver.clone() {
                 packages_delivered.push(package);
             }
-            i += 1;
         }
 
-        return (
+        (
             new_movements.clone(),
             packages_picked_up.clone(),
             packages_delivered.clone(),
-        );
+        )
+    }
+    // Sends every train that moved in the plan and has an end station back to
+    // it once its last delivery is done.
+    pub fn move_trains_to_end(
+        &mut self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> Vec<Movement> {
+        let mut train_names: Vec<String> = trains.keys().cloned().collect();
+        train_names.sort();
+        let mut new_movements = movements;
+        for train_name in train_names {
+            let mut train = trains[&train_name].clone();
+            if train.end.is_empty()
+                || train.current_location == train.end
+                || !new_movements
+                    .iter()
+                    .any(|movement| movement.train == train.name)
+            {
+                continue;
+            }
+            let departure_time = Navigation::get_departure_time(
+                train.clone(),
+                packages.clone(),
+                new_movements.clone(),
+            );
+            let destination = self.get_train_destination(&train, train.end.clone(), departure_time);
+            let first_new = new_movements.len();
+            new_movements =
+                Navigation::move_train(train.clone(), destination, packages.clone(), new_movements)
+                    .0;
+            new_movements = self.add_rest_breaks(&mut train, new_movements, first_new);
+        }
+        new_movements
     }
     pub fn get_longest_distance_in_movements(movements: Vec<Movement>) -> i32 {
         let mut longest_distance = 0;
@@ -288,67 +730,121 @@ impl Navigation {
         packages: HashMap<String, Package>,
         movements: Vec<Movement>,
     ) -> Vec<Movement> {
+        match self.try_calculate(trains, packages, movements) {
+            Some(movements) => movements,
+            None => panic!("No solution found"),
+        }
+    }
+    // Like `calculate`, but `None` when no plan delivers every package
+    // without a train going over its limits.
+    pub fn try_calculate(
+        &mut self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> Option<Vec<Movement>> {
+        self.stats.states_explored += 1;
+        if self
+            .stats
+            .states_explored
+            .is_multiple_of(self.progress_interval.max(1))
+        {
+            if let Some(progress) = self.progress.as_mut() {
+                progress(&self.stats);
+            }
+        }
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        let best_movements = self.calculate_state(trains, packages, movements);
+        self.depth -= 1;
+        best_movements
+    }
+    fn calculate_state(
+        &mut self,
+        trains: HashMap<String, Train>,
+        packages: HashMap<String, Package>,
+        movements: Vec<Movement>,
+    ) -> Option<Vec<Movement>> {
         let mut min_distance: i32 = i32::MAX;
         let mut min_trains: i32 = i32::MAX;
+        // Always 0 unless the objective is the cost.
+        let mut min_cost: i32 = i32::MAX;
         let mut total_combinations: i32 = 0;
-        let cache_key = Navigation::get_cache_key(trains.clone(), packages.clone());
+        let cache_key = Navigation::get_cache_key(trains.clone(), packages.clone(), &movements);
         let mut best_movements: Vec<Movement> = movements.clone();
-        let mut cache_found = false;
-        match self.cache.get(&cache_key) {
-            Some(cached_movements) => {
-                best_movements = cached_movements.clone();
-                cache_found = true;
-            }
-            None => {}
-        };
-
-        if cache_found {
-            return best_movements;
+        if let Some(cached_movements) = self.cache.get(&cache_key) {
+            self.stats.memo_hits += 1;
+            return cached_movements.clone();
         }
+        self.stats.memo_misses += 1;
 
-        let mut queue: Vec<(String, String, bool)> = vec![];
+        // (train, package, pick up, hub to leave the package at or "").
+        let mut queue: Vec<(String, String, bool, String)> = vec![];
 
         for (_, package) in packages.clone() {
-            if package.to_be_picked_up_by != ""
-                || package.picked_up_by != ""
-                || package.delivered_by != ""
+            if !package.to_be_picked_up_by.is_empty()
+                || !package.picked_up_by.is_empty()
+                || !package.delivered_by.is_empty()
             {
                 continue;
             }
 
             let capable_trains =
                 Navigation::get_capable_trains(package.clone(), packages.clone(), trains.clone());
+            self.stats.branches_pruned += trains.len() - capable_trains.len();
             for train in capable_trains.clone() {
-                queue.push((train.name.clone(), package.name.clone(), true));
+                queue.push((
+                    train.name.clone(),
+                    package.name.clone(),
+                    true,
+                    "".to_string(),
+                ));
             }
         }
 
         for (_, train) in trains.clone() {
-            for package_name in train.packages_to_pick_up {
-                queue.push((train.name.clone(), package_name.clone(), false));
-            }
-            for package_name in train.packages_picked_up {
-                queue.push((train.name.clone(), package_name.clone(), false));
+            let mut train_packages = train.packages_to_pick_up.clone();
+            train_packages.extend(train.packages_picked_up.clone());
+            for package_name in train_packages {
+                queue.push((
+                    train.name.clone(),
+                    package_name.clone(),
+                    false,
+                    "".to_string(),
+                ));
+                let package = match packages.get(&package_name) {
+                    Some(package) => package,
+                    None => panic!("Package not found"),
+                };
+                for hub in self.hubs.iter() {
+                    if *hub != train.current_location && package.can_transfer_at(hub) {
+                        queue.push((train.name.clone(), package_name.clone(), false, hub.clone()));
+                    }
+                }
             }
         }
 
         queue.sort_by(
-            |(train_name_a, package_name_a, to_pick_up_a),
-             (train_name_b, package_name_b, to_pick_up_b)| {
+            |(train_name_a, package_name_a, to_pick_up_a, hub_a),
+             (train_name_b, package_name_b, to_pick_up_b, hub_b)| {
                 let train_name_cmp = train_name_a.cmp(train_name_b);
                 let package_name_cmp = package_name_a.cmp(package_name_b);
                 let to_pick_up_cmp = to_pick_up_a.cmp(to_pick_up_b);
                 if to_pick_up_cmp == Ordering::Equal {
                     if train_name_cmp == Ordering::Equal {
+                        if package_name_cmp == Ordering::Equal {
+                            return hub_a.cmp(hub_b);
+                        }
                         return package_name_cmp;
                     }
                     return train_name_cmp;
                 }
-                return to_pick_up_cmp;
+                to_pick_up_cmp
             },
         );
 
-        for (train_name, package_name, to_pick_up) in queue {
+        let mut best_trace_index: Option<usize> = None;
+        for (train_name, package_name, to_pick_up, hub) in queue {
             let mut new_trains = trains.clone();
             let mut new_packages = packages.clone();
             let mut new_train = match new_trains.get(&train_name) {
@@ -359,21 +855,32 @@ impl Navigation {
                 Some(package) => package.clone(),
                 None => panic!("Package not found"),
             };
-            let mut destination = self
-                .graph
-                .get_destination(new_train.current_location.clone(), new_package.to.clone());
-            if to_pick_up {
-                destination = self
-                    .graph
-                    .get_destination(new_train.current_location.clone(), new_package.from.clone());
-            }
-
-            let (new_movements, packages_picked_up, packages_delivered) = Navigation::move_train(
+            let departure_time = Navigation::get_departure_time(
                 new_train.clone(),
-                destination.clone(),
                 new_packages.clone(),
                 movements.clone(),
             );
+            let target = if !hub.is_empty() {
+                hub.clone()
+            } else if to_pick_up {
+                new_package.location.clone()
+            } else {
+                new_package.to.clone()
+            };
+            let destination = self.get_train_destination(&new_train, target, departure_time);
+
+            let (mut new_movements, packages_picked_up, packages_delivered) =
+                Navigation::move_train(
+                    new_train.clone(),
+                    destination.clone(),
+                    new_packages.clone(),
+                    movements.clone(),
+                );
+            new_movements = self.add_rest_breaks(&mut new_train, new_movements, movements.len());
+            if new_train.breaks_limits(&new_movements) {
+                self.stats.branches_pruned += 1;
+                continue;
+            }
 
             if destination.cumulative_distance > 0 {
                 new_train.current_location = destination.to;
@@ -407,13 +914,10 @@ impl Navigation {
                     new_packages.insert(
                         package_name.clone(),
                         Package {
-                            name: package_name.clone(),
-                            from: package.from.clone(),
-                            to: package.to.clone(),
-                            weight: package.weight,
                             to_be_picked_up_by: new_train.name.clone(),
                             picked_up_by: new_train.name.clone(),
                             delivered_by: "".to_string(),
+                            ..package.clone()
                         },
                     );
                 }
@@ -440,56 +944,121 @@ impl Navigation {
                     new_packages.insert(
                         package_name.clone(),
                         Package {
-                            name: package_name.clone(),
-                            from: package.from.clone(),
-                            to: package.to.clone(),
-                            weight: package.weight,
                             to_be_picked_up_by: new_train.name.clone(),
                             picked_up_by: new_train.name.clone(),
                             delivered_by: new_train.name.clone(),
+                            ..package.clone()
                         },
                     );
                 }
             }
 
+            // Leaves the package at the hub for another train, unless the
+            // way there went past its destination.
+            if !hub.is_empty() && new_package.delivered_by.is_empty() {
+                if let Some(movement) = new_movements.last_mut() {
+                    movement.packages_transferred.push(new_package.name.clone());
+                    new_package.available_at = movement.end_time;
+                }
+                new_train
+                    .packages_picked_up
+                    .retain(|package_picked_up| *package_picked_up != new_package.name);
+                new_package.to_be_picked_up_by = "".to_string();
+                new_package.picked_up_by = "".to_string();
+                new_package.location = hub.clone();
+                new_package.legs.push((new_train.name.clone(), hub.clone()));
+            }
+
             new_trains.insert(new_train.name.clone(), new_train.clone());
             new_packages.insert(new_package.name.clone(), new_package.clone());
 
-            let all_movements = self.calculate(
+            let all_movements = match self.try_calculate(
                 new_trains.clone(),
                 new_packages.clone(),
                 new_movements.clone(),
-            );
+            ) {
+                Some(all_movements) => all_movements,
+                // Every way on goes over a train's limits.
+                None => {
+                    self.stats.branches_pruned += 1;
+                    continue;
+                }
+            };
             let longest_train_distance =
                 Navigation::get_longest_distance_in_movements(all_movements.clone());
             let number_of_trains = Navigation::get_number_of_trains(all_movements.clone());
-
-            if longest_train_distance < min_distance
-                || (longest_train_distance == min_distance && number_of_trains < min_trains)
+            let cost = match self.objective {
+                Objective::Makespan => 0,
+                Objective::Cost => self.get_cost(&all_movements),
+            };
+            let trace_index = self.trace_decision(
+                (self.depth, &cache_key),
+                (&train_name, &package_name, to_pick_up, &hub),
+                (cost, longest_train_distance, number_of_trains),
+                (min_cost, min_distance, min_trains),
+            );
+            if (cost, longest_train_distance, number_of_trains)
+                < (min_cost, min_distance, min_trains)
             {
+                best_trace_index = trace_index;
+                min_cost = cost;
                 min_distance = longest_train_distance;
                 min_trains = number_of_trains;
                 best_movements = all_movements.clone();
+            } else {
+                self.stats.branches_discarded += 1;
             }
+            self.stats.branches_explored += 1;
             total_combinations += 1;
         }
+        if let (Some(trace), Some(index)) = (self.trace.as_mut(), best_trace_index) {
+            trace[index].kept = true;
+        }
 
         // If no combinations left, it could mean either:
         if total_combinations == 0 {
             let mut undelivered_packages: Vec<String> = vec![];
-            for (_, package) in packages {
-                if package.delivered_by == "" {
+            for package in packages.values() {
+                if package.delivered_by.is_empty() {
                     undelivered_packages.push(package.name.clone());
                 }
             }
 
             // All packages are delivered
-            if undelivered_packages.len() == 0 {
-                return best_movements;
-            // Or something is wrong
-            } else {
-                panic!("No solution found");
+            if undelivered_packages.is_empty() {
+                best_movements = self.move_trains_to_end(trains.clone(), packages, best_movements);
+                if trains
+                    .values()
+                    .any(|train| train.breaks_limits(&best_movements))
+                {
+                    self.cache.insert(cache_key, None);
+                    return None;
+                }
+                let makespan =
+                    Navigation::get_longest_distance_in_movements(best_movements.clone());
+                let number_of_trains = Navigation::get_number_of_trains(best_movements.clone());
+                let cost = match self.objective {
+                    Objective::Makespan => None,
+                    Objective::Cost => Some(self.get_cost(&best_movements)),
+                };
+                let is_best = match (self.stats.best_makespan, self.stats.best_number_of_trains) {
+                    (Some(best_makespan), Some(best_number_of_trains)) => {
+                        (cost, makespan, number_of_trains)
+                            < (self.stats.best_cost, best_makespan, best_number_of_trains)
+                    }
+                    _ => true,
+                };
+                if is_best {
+                    self.stats.best_cost = cost;
+                    self.stats.best_makespan = Some(makespan);
+                    self.stats.best_number_of_trains = Some(number_of_trains);
+                }
+                return Some(best_movements);
             }
+            // Or every job left goes over a train's limits, or nothing can
+            // carry the packages left
+            self.cache.insert(cache_key, None);
+            return None;
         }
 
         best_movements.sort_by(|movement_a, movement_b| {
@@ -498,19 +1067,39 @@ impl Navigation {
             if train_cmp == Ordering::Equal {
                 return start_time_cmp;
             }
-            return train_cmp;
+            train_cmp
         });
-        self.cache.insert(cache_key.clone(), best_movements.clone());
-        return best_movements;
+        self.cache
+            .insert(cache_key.clone(), Some(best_movements.clone()));
+        Some(best_movements)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{input::Input, movement::Movement};
+    use crate::{input::Input, movement::Movement, validation::ValidationError};
 
     use super::Navigation;
 
+    // Two packages waiting at B for C, with one train at B and one at A.
+    fn create_input() -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "B".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "B".to_string(), "C".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "B".to_string()),
+                ("Q2".to_string(), 10, "A".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_basic_navigation() {
         let mut navigation = Navigation::new(Input {
@@ -520,6 +1109,7 @@ mod tests {
             ],
             packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
             trains: vec![("Q1".to_string(), 6, "B".to_string())],
+            ..Default::default()
         });
         let movements = navigation.calculate(
             navigation.trains.clone(),
@@ -534,27 +1124,89 @@ mod tests {
                     end_time: 30,
                     from: "B".to_string(),
                     to: "A".to_string(),
+                    edge: "E1".to_string(),
                     train: "Q1".to_string(),
                     packages_picked_up: vec![],
                     packages_delivered: vec![],
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 30,
                     end_time: 60,
                     from: "A".to_string(),
                     to: "B".to_string(),
+                    edge: "E1".to_string(),
                     train: "Q1".to_string(),
                     packages_picked_up: vec!["K1".to_string()],
                     packages_delivered: vec![],
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 60,
                     end_time: 70,
                     from: "B".to_string(),
                     to: "C".to_string(),
+                    edge: "E2".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
+                }
+            ]
+        );
+    }
+    #[test]
+    fn test_navigation_waiting_for_closure() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "B".to_string())],
+            closures: vec![("E2".to_string(), 40, 65)],
+            ..Default::default()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            movements,
+            vec![
+                Movement {
+                    start_time: 0,
+                    end_time: 30,
+                    from: "B".to_string(),
+                    to: "A".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 30,
+                    end_time: 60,
+                    from: "A".to_string(),
+                    to: "B".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 65,
+                    end_time: 75,
+                    from: "B".to_string(),
+                    to: "C".to_string(),
+                    edge: "E2".to_string(),
                     train: "Q1".to_string(),
                     packages_picked_up: vec![],
                     packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
                 }
             ]
         );
@@ -580,6 +1232,7 @@ mod tests {
                 ("Q2".to_string(), 15, "B".to_string()),
                 ("Q3".to_string(), 15, "C".to_string()),
             ],
+            ..Default::default()
         });
         let movements = navigation.calculate(
             navigation.trains.clone(),
@@ -594,58 +1247,483 @@ mod tests {
                     end_time: 10,
                     from: "A".to_string(),
                     to: "X".to_string(),
+                    edge: "E1".to_string(),
                     train: "Q1".to_string(),
                     packages_picked_up: [].to_vec(),
                     packages_delivered: [].to_vec(),
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 10,
                     end_time: 20,
                     from: "X".to_string(),
                     to: "D".to_string(),
+                    edge: "E4".to_string(),
                     train: "Q1".to_string(),
                     packages_picked_up: ["K1".to_string()].to_vec(),
                     packages_delivered: ["K1".to_string()].to_vec(),
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 0,
                     end_time: 10,
                     from: "B".to_string(),
                     to: "X".to_string(),
+                    edge: "E2".to_string(),
                     train: "Q2".to_string(),
                     packages_picked_up: [].to_vec(),
                     packages_delivered: [].to_vec(),
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 10,
                     end_time: 20,
                     from: "X".to_string(),
                     to: "E".to_string(),
+                    edge: "E5".to_string(),
                     train: "Q2".to_string(),
                     packages_picked_up: ["K2".to_string()].to_vec(),
                     packages_delivered: ["K2".to_string()].to_vec(),
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 0,
                     end_time: 10,
                     from: "C".to_string(),
                     to: "X".to_string(),
+                    edge: "E3".to_string(),
                     train: "Q3".to_string(),
                     packages_picked_up: [].to_vec(),
                     packages_delivered: [].to_vec(),
+                    packages_transferred: vec![],
                 },
                 Movement {
                     start_time: 10,
                     end_time: 20,
                     from: "X".to_string(),
                     to: "F".to_string(),
+                    edge: "E6".to_string(),
                     train: "Q3".to_string(),
                     packages_picked_up: ["K3".to_string()].to_vec(),
                     packages_delivered: ["K3".to_string()].to_vec(),
+                    packages_transferred: vec![],
                 },
             ],
         );
     }
+    #[test]
+    fn test_pinned_and_forbidden_assignments() {
+        let trains = vec![
+            ("Q1".to_string(), 10, "B".to_string()),
+            ("Q2".to_string(), 10, "A".to_string()),
+            ("Q3".to_string(), 10, "A".to_string()),
+        ];
+        let mut navigation = Navigation::new(Input {
+            trains: trains.clone(),
+            must_not_assign: vec![("K1".to_string(), "Q1".to_string())],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let carriers: Vec<(String, Vec<String>)> = movements
+            .iter()
+            .filter(|movement| !movement.packages_picked_up.is_empty())
+            .map(|movement| (movement.train.clone(), movement.packages_picked_up.clone()))
+            .collect();
+        assert_eq!(
+            carriers,
+            vec![("Q2".to_string(), vec!["K1".to_string(), "K2".to_string()])]
+        );
+        assert_eq!(navigation.validate_plan(movements), vec![]);
+
+        let mut navigation = Navigation::new(Input {
+            trains: trains.clone(),
+            must_assign: vec![
+                ("K1".to_string(), "Q3".to_string()),
+                ("K2".to_string(), "Q3".to_string()),
+            ],
+            must_not_assign: vec![("K1".to_string(), "Q1".to_string())],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert!(movements.iter().all(|movement| movement.train == "Q3"));
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 20);
+
+        let navigation = Navigation::new(Input {
+            trains,
+            must_not_assign: vec![
+                ("K1".to_string(), "Q1".to_string()),
+                ("K1".to_string(), "Q2".to_string()),
+                ("K1".to_string(), "Q3".to_string()),
+            ],
+            ..create_input()
+        });
+        assert_eq!(
+            navigation.validate_input(),
+            vec![ValidationError::NoAllowedTrain {
+                package: "K1".to_string()
+            }]
+        );
+    }
+    #[test]
+    fn test_package_attributes() {
+        let mut navigation = Navigation::new(Input {
+            trains: vec![
+                ("Q1".to_string(), 20, "B".to_string()),
+                ("Q2".to_string(), 20, "A".to_string()),
+            ],
+            package_attributes: vec![
+                ("K1".to_string(), "refrigerated".to_string()),
+                ("K2".to_string(), "hazardous".to_string()),
+            ],
+            train_attributes: vec![
+                ("Q1".to_string(), "refrigerated".to_string()),
+                ("Q1".to_string(), "hazardous".to_string()),
+                ("Q2".to_string(), "hazardous".to_string()),
+            ],
+            incompatible_attributes: vec![("refrigerated".to_string(), "hazardous".to_string())],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let carriers: Vec<(String, Vec<String>)> = movements
+            .iter()
+            .filter(|movement| !movement.packages_picked_up.is_empty())
+            .map(|movement| (movement.train.clone(), movement.packages_picked_up.clone()))
+            .collect();
+        assert_eq!(
+            carriers,
+            vec![
+                ("Q1".to_string(), vec!["K1".to_string()]),
+                ("Q2".to_string(), vec!["K2".to_string()]),
+            ]
+        );
+        assert_eq!(navigation.validate_plan(movements), vec![]);
+        assert_eq!(
+            navigation.validate_plan(vec![Movement {
+                start_time: 0,
+                end_time: 10,
+                from: "B".to_string(),
+                to: "C".to_string(),
+                edge: "E2".to_string(),
+                train: "Q1".to_string(),
+                packages_picked_up: vec!["K1".to_string(), "K2".to_string()],
+                packages_delivered: vec!["K1".to_string(), "K2".to_string()],
+                packages_transferred: vec![],
+            }]),
+            vec![ValidationError::IncompatiblePackages {
+                package: "K2".to_string(),
+                other_package: "K1".to_string(),
+                train: "Q1".to_string(),
+                time: 0,
+            }]
+        );
+
+        let navigation = Navigation::new(Input {
+            trains: vec![
+                ("Q1".to_string(), 20, "B".to_string()),
+                ("Q2".to_string(), 20, "A".to_string()),
+            ],
+            package_attributes: vec![
+                ("K1".to_string(), "refrigerated".to_string()),
+                ("K2".to_string(), "oversize".to_string()),
+            ],
+            train_attributes: vec![
+                ("Q1".to_string(), "refrigerated".to_string()),
+                ("Q1".to_string(), "hazardous".to_string()),
+                ("Q2".to_string(), "hazardous".to_string()),
+            ],
+            ..create_input()
+        });
+        assert_eq!(
+            navigation.validate_input(),
+            vec![ValidationError::NoSupportingTrain {
+                package: "K2".to_string()
+            }]
+        );
+    }
+    #[test]
+    fn test_capacity_dimensions() {
+        let train_capacities = vec![
+            ("Q1".to_string(), "volume".to_string(), 10),
+            ("Q2".to_string(), "volume".to_string(), 8),
+            ("Q2".to_string(), "slots".to_string(), 2),
+        ];
+        let mut navigation = Navigation::new(Input {
+            dimensions: vec!["volume".to_string(), "slots".to_string()],
+            train_capacities: train_capacities.clone(),
+            package_sizes: vec![
+                ("K1".to_string(), "volume".to_string(), 6),
+                ("K2".to_string(), "volume".to_string(), 6),
+                ("K1".to_string(), "slots".to_string(), 1),
+                ("K2".to_string(), "slots".to_string(), 1),
+            ],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let carriers: Vec<(String, Vec<String>)> = movements
+            .iter()
+            .filter(|movement| !movement.packages_picked_up.is_empty())
+            .map(|movement| (movement.train.clone(), movement.packages_picked_up.clone()))
+            .collect();
+        assert_eq!(carriers.len(), 2);
+        assert_eq!(
+            Navigation::get_longest_distance_in_movements(movements.clone()),
+            20
+        );
+        assert_eq!(navigation.validate_plan(movements), vec![]);
+        assert_eq!(
+            navigation.validate_plan(vec![Movement {
+                start_time: 0,
+                end_time: 10,
+                from: "B".to_string(),
+                to: "C".to_string(),
+                edge: "E2".to_string(),
+                train: "Q1".to_string(),
+                packages_picked_up: vec!["K1".to_string(), "K2".to_string()],
+                packages_delivered: vec!["K1".to_string(), "K2".to_string()],
+                packages_transferred: vec![],
+            }]),
+            vec![ValidationError::DimensionOverCapacity {
+                train: "Q1".to_string(),
+                dimension: "volume".to_string(),
+                load: 12,
+                time: 0,
+            }]
+        );
+
+        let navigation = Navigation::new(Input {
+            dimensions: vec!["volume".to_string(), "slots".to_string()],
+            train_capacities,
+            package_sizes: vec![
+                ("K1".to_string(), "volume".to_string(), 6),
+                ("K2".to_string(), "volume".to_string(), 11),
+            ],
+            ..create_input()
+        });
+        assert_eq!(
+            navigation.validate_input(),
+            vec![ValidationError::PackageTooLarge {
+                package: "K2".to_string()
+            }]
+        );
+    }
+    #[test]
+    fn test_train_end_stations() {
+        let mut navigation = Navigation::new(Input {
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 10, "A".to_string())],
+            train_ends: vec![("Q1".to_string(), "B".to_string())],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let legs: Vec<(String, String, i32)> = movements
+            .iter()
+            .map(|movement| {
+                (
+                    movement.from.clone(),
+                    movement.to.clone(),
+                    movement.end_time,
+                )
+            })
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                ("A".to_string(), "B".to_string(), 10),
+                ("B".to_string(), "C".to_string(), 20),
+                ("C".to_string(), "B".to_string(), 30),
+            ]
+        );
+        assert_eq!(navigation.validate_plan(movements.clone()), vec![]);
+        assert_eq!(
+            navigation.validate_plan(movements[..2].to_vec()),
+            vec![ValidationError::TrainNotAtEnd {
+                train: "Q1".to_string(),
+                station: "B".to_string(),
+            }]
+        );
+
+        let mut navigation = Navigation::new(Input {
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 10, "A".to_string())],
+            return_to_start: true,
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(movements.last().unwrap().to, "A");
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 40);
+    }
+    #[test]
+    fn test_train_departure_times() {
+        let mut navigation = Navigation::new(Input {
+            packages: vec![("K1".to_string(), 5, "B".to_string(), "C".to_string())],
+            train_departures: vec![("Q1".to_string(), 50)],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert!(movements.iter().all(|movement| movement.train == "Q2"));
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 20);
+
+        let mut navigation = Navigation::new(Input {
+            packages: vec![("K1".to_string(), 5, "B".to_string(), "C".to_string())],
+            train_departures: vec![("Q1".to_string(), 5)],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            movements,
+            vec![Movement {
+                start_time: 5,
+                end_time: 15,
+                from: "B".to_string(),
+                to: "C".to_string(),
+                edge: "E2".to_string(),
+                train: "Q1".to_string(),
+                packages_picked_up: vec!["K1".to_string()],
+                packages_delivered: vec!["K1".to_string()],
+                packages_transferred: vec![],
+            }]
+        );
+        assert_eq!(navigation.validate_plan(movements.clone()), vec![]);
+        assert_eq!(
+            navigation.validate_plan(vec![Movement {
+                start_time: 0,
+                end_time: 10,
+                ..movements[0].clone()
+            }]),
+            vec![ValidationError::TrainNotAvailable {
+                train: "Q1".to_string(),
+                time: 0,
+            }]
+        );
+    }
+    #[test]
+    fn test_transfers_at_hubs() {
+        let mut without_hubs = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "H".to_string(), 10),
+                ("E2".to_string(), "H".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "H".to_string(), "D".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "A".to_string(), "D".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "A".to_string()),
+                ("Q2".to_string(), 10, "H".to_string()),
+            ],
+            ..Default::default()
+        });
+        let movements = without_hubs.calculate(
+            without_hubs.trains.clone(),
+            without_hubs.packages.clone(),
+            vec![],
+        );
+        assert_eq!(Navigation::get_longest_distance_in_movements(movements), 30);
+
+        let mut navigation = Navigation::build(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "H".to_string(), 10),
+                ("E2".to_string(), "H".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "H".to_string(), "D".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "A".to_string(), "D".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "A".to_string()),
+                ("Q2".to_string(), 10, "H".to_string()),
+            ],
+            hubs: vec!["H".to_string()],
+            ..Default::default()
+        });
+        navigation.refresh_distance_matrix();
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(
+            movements,
+            vec![
+                Movement {
+                    start_time: 0,
+                    end_time: 10,
+                    from: "A".to_string(),
+                    to: "H".to_string(),
+                    edge: "E1".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec!["K1".to_string(), "K2".to_string()],
+                    packages_delivered: vec![],
+                    packages_transferred: vec!["K1".to_string()],
+                },
+                Movement {
+                    start_time: 10,
+                    end_time: 20,
+                    from: "H".to_string(),
+                    to: "D".to_string(),
+                    edge: "E3".to_string(),
+                    train: "Q1".to_string(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec!["K2".to_string()],
+                    packages_transferred: vec![],
+                },
+                Movement {
+                    start_time: 10,
+                    end_time: 20,
+                    from: "H".to_string(),
+                    to: "C".to_string(),
+                    edge: "E2".to_string(),
+                    train: "Q2".to_string(),
+                    packages_picked_up: vec!["K1".to_string()],
+                    packages_delivered: vec!["K1".to_string()],
+                    packages_transferred: vec![],
+                },
+            ]
+        );
+        assert_eq!(navigation.validate_plan(movements.clone()), vec![]);
+        assert_eq!(
+            without_hubs.validate_plan(movements),
+            vec![ValidationError::TransferNotAtHub {
+                package: "K1".to_string(),
+                station: "H".to_string(),
+                time: 0,
+            }]
+        );
+    }
     // #[test]
     // fn test_delivering_more_packages_using_multiple_trains_in_parallel() {
     //     let mut navigation = Navigation::new(Input {
@@ -673,6 +1751,7 @@ mod tests {
     //             ("Q1".to_string(), 20, "B".to_string()),
     //             ("Q2".to_string(), 20, "C".to_string()),
     //         ],
+    //         ..Default::default()
     //     });
     //     let movements = navigation.calculate(
     //         navigation.trains.clone(),
@@ -1,9 +1,28 @@
+use crate::train::Train;
+
 #[derive(Clone, Debug)]
 pub struct Package {
     pub name: String,
     pub from: String,
     pub to: String,
     pub weight: i32,
+    // Sizes in the dimensions beyond weight, in the order of
+    // `Navigation::dimensions`.
+    pub sizes: Vec<i32>,
+    pub available_at: i32,
+    // Where the package waits to be picked up: `from`, or the hub it was
+    // last left at.
+    pub location: String,
+    // (train, hub) for every leg that ended at a hub.
+    pub legs: Vec<(String, String)>,
+    // The only train allowed to carry the package, "" for any.
+    pub pinned_to: String,
+    // Trains never allowed to carry the package.
+    pub forbidden_trains: Vec<String>,
+    // What the train has to support to carry the package.
+    pub required_attributes: Vec<String>,
+    // Attributes of other packages it may not share a train with.
+    pub incompatible_attributes: Vec<String>,
     pub to_be_picked_up_by: String,
     pub picked_up_by: String,
     pub delivered_by: String,
@@ -16,9 +35,56 @@ impl Package {
             from: from.clone(),
             to: to.clone(),
             weight,
+            sizes: [].to_vec(),
+            available_at: 0,
+            location: from.clone(),
+            legs: [].to_vec(),
+            pinned_to: "".to_string(),
+            forbidden_trains: [].to_vec(),
+            required_attributes: [].to_vec(),
+            incompatible_attributes: [].to_vec(),
             to_be_picked_up_by: "".to_string(),
             picked_up_by: "".to_string(),
             delivered_by: "".to_string(),
         }
     }
+    pub fn is_allowed_on(&self, train: &str) -> bool {
+        (self.pinned_to.is_empty() || self.pinned_to == train)
+            && !self
+                .forbidden_trains
+                .iter()
+                .any(|forbidden| forbidden == train)
+    }
+    // Whether the package fits on the train next to `load`, the sizes of the
+    // packages already there, in every dimension beyond weight. Missing
+    // capacities are unlimited and missing sizes are 0.
+    pub fn fits_next_to(&self, load: &[i32], train: &Train) -> bool {
+        self.sizes.iter().enumerate().all(|(i, size)| {
+            let capacity = train.capacities.get(i).copied().unwrap_or(i32::MAX);
+            *size <= capacity - load.get(i).copied().unwrap_or(0)
+        })
+    }
+    pub fn fits_in(&self, train: &Train) -> bool {
+        train.capacity >= self.weight && self.fits_next_to(&[], train)
+    }
+    // A package is never left where it has already been, so it cannot go
+    // round in circles.
+    pub fn can_transfer_at(&self, hub: &str) -> bool {
+        hub != self.from && hub != self.to && !self.legs.iter().any(|(_, station)| station == hub)
+    }
+    pub fn is_supported_by(&self, train: &Train) -> bool {
+        self.required_attributes
+            .iter()
+            .all(|attribute| train.attributes.contains(attribute))
+    }
+    pub fn is_compatible_with(&self, package: &Package) -> bool {
+        !self
+            .incompatible_attributes
+            .iter()
+            .any(|attribute| package.required_attributes.contains(attribute))
+            && !package
+                .incompatible_attributes
+                .iter()
+                .any(|attribute| self.required_attributes.contains(attribute))
+    }
 }
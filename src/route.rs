@@ -2,4 +2,6 @@
 pub struct Route {
     pub to: String,
     pub distance: i32,
+    // The edge the leg goes over.
+    pub edge: String,
 }
@@ -0,0 +1,706 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{
+    cost::{Objective, PlanSummary},
+    destination::Destination,
+    graph::Graph,
+    input::Input,
+    json::Json,
+    movement::Movement,
+    navigation::Navigation,
+};
+
+// Larger bodies are answered with 413 without being read.
+const MAX_BODY_LENGTH: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProblemStatus {
+    Running,
+    Solved(Vec<Movement>, PlanSummary),
+    Failed(String),
+}
+
+pub struct ServerState {
+    pub graph: Graph,
+    pub edges: HashMap<String, (String, String, i32)>,
+    // Bumped every time the network is replaced, so solves started on an older
+    // network do not bring their shortest paths back into the new one.
+    pub network_version: usize,
+    pub problems: HashMap<usize, ProblemStatus>,
+}
+
+// Keeps one network loaded with its shortest path cache warm, and solves
+// problems on it in the background. Bodies and responses are JSON.
+//
+// POST /network        {"edges": [{"name", "from", "to", "distance"}],
+//                       "closures": [{"edge", "start", "end"}]}
+// POST /problems       {"trains": [{"name", "capacity", "start", "available_at"?,
+//                                   "fixed_cost"?, "cost_per_distance"?}],
+//                       "packages": [{"name", "weight", "from", "to"}],
+//                       "hubs"?: [station], "tolls"?: [{"edge", "toll"}],
+//                       "objective"?: "makespan" | "cost"}
+// GET  /problems/{id}  {"id", "status", "movements" and "summary" | "error"}
+// POST /validate       the problem with its "movements", gives the "errors"
+// GET  /paths?from=A&to=B
+pub struct Server {
+    pub state: Arc<Mutex<ServerState>>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server {
+            state: Arc::new(Mutex::new(ServerState {
+                graph: Graph::new(),
+                edges: HashMap::new(),
+                network_version: 0,
+                problems: HashMap::new(),
+            })),
+        }
+    }
+
+    pub fn serve(&self, listener: TcpListener) {
+        for stream in listener.incoming().flatten() {
+            let state = self.state.clone();
+            thread::spawn(move || Server::handle_connection(state, stream));
+        }
+    }
+
+    fn handle_connection(state: Arc<Mutex<ServerState>>, stream: TcpStream) {
+        let mut reader = BufReader::new(match stream.try_clone() {
+            Ok(stream) => stream,
+            Err(_) => return,
+        });
+        let mut request_line = "".to_string();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        let mut content_length = 0;
+        loop {
+            let mut header = "".to_string();
+            if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let (status, json) = if content_length > MAX_BODY_LENGTH {
+            (
+                413,
+                Json::Object(vec![("error".to_string(), Json::from("Body too large"))]),
+            )
+        } else {
+            let mut body = vec![0; content_length];
+            if reader.read_exact(&mut body).is_err() {
+                return;
+            }
+            let mut parts = request_line.split_whitespace();
+            let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+            Server::handle_request(&state, method, path, &String::from_utf8_lossy(&body))
+        };
+        let reason = match status {
+            200 => "OK",
+            202 => "Accepted",
+            400 => "Bad Request",
+            413 => "Payload Too Large",
+            _ => "Not Found",
+        };
+        let body = json.to_string();
+        let mut stream = stream;
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        );
+    }
+
+    pub fn handle_request(
+        state: &Arc<Mutex<ServerState>>,
+        method: &str,
+        path: &str,
+        body: &str,
+    ) -> (u16, Json) {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let result = match (method, path) {
+            ("POST", "/network") => Server::load_network(state, body),
+            ("POST", "/problems") => Server::submit_problem(state, body),
+            ("GET", path) if path.starts_with("/problems/") => {
+                Server::get_problem(state, &path["/problems/".len()..])
+            }
+            ("POST", "/validate") => Server::validate_plan(state, body),
+            ("GET", "/paths") => Server::get_path(state, query),
+            _ => Err((404, "Not found".to_string())),
+        };
+        match result {
+            Ok(response) => response,
+            Err((status, error)) => (
+                status,
+                Json::Object(vec![("error".to_string(), Json::from(error))]),
+            ),
+        }
+    }
+
+    fn parse_body(body: &str) -> Result<Json, (u16, String)> {
+        Json::parse(body).map_err(|error| (400, error))
+    }
+
+    fn get_items(json: &Json, key: &str) -> Result<Vec<Json>, (u16, String)> {
+        match json.get(key) {
+            Some(Json::Array(items)) => Ok(items.clone()),
+            None => Ok(vec![]),
+            Some(_) => Err((400, format!("{} must be a list", key))),
+        }
+    }
+
+    fn get_string(json: &Json, key: &str) -> Result<String, (u16, String)> {
+        match json.get(key).and_then(Json::as_str) {
+            Some(value) => Ok(value.to_string()),
+            None => Err((400, format!("{} missing", key))),
+        }
+    }
+
+    fn get_number(json: &Json, key: &str) -> Result<i32, (u16, String)> {
+        match json.get(key).map(Json::as_i32) {
+            Some(Some(value)) => Ok(value),
+            Some(None) => Err((400, format!("{} must be an integer", key))),
+            None => Err((400, format!("{} missing", key))),
+        }
+    }
+
+    fn get_optional_number(json: &Json, key: &str) -> Result<i32, (u16, String)> {
+        match json.get(key) {
+            Some(_) => Server::get_non_negative_number(json, key),
+            None => Ok(0),
+        }
+    }
+
+    fn get_non_negative_number(json: &Json, key: &str) -> Result<i32, (u16, String)> {
+        match Server::get_number(json, key)? {
+            value if value < 0 => Err((400, format!("{} must not be negative", key))),
+            value => Ok(value),
+        }
+    }
+
+    fn load_network(
+        state: &Arc<Mutex<ServerState>>,
+        body: &str,
+    ) -> Result<(u16, Json), (u16, String)> {
+        let json = Server::parse_body(body)?;
+        let mut edges: Vec<(String, String, String, i32)> = vec![];
+        for edge in Server::get_items(&json, "edges")? {
+            edges.push((
+                Server::get_string(&edge, "name")?,
+                Server::get_string(&edge, "from")?,
+                Server::get_string(&edge, "to")?,
+                Server::get_non_negative_number(&edge, "distance")?,
+            ));
+        }
+        let mut closures: Vec<(String, i32, i32)> = vec![];
+        for closure in Server::get_items(&json, "closures")? {
+            let edge = Server::get_string(&closure, "edge")?;
+            if !edges.iter().any(|(name, _, _, _)| *name == edge) {
+                return Err((400, "Edge not found".to_string()));
+            }
+            closures.push((
+                edge,
+                Server::get_number(&closure, "start")?,
+                Server::get_number(&closure, "end")?,
+            ));
+        }
+
+        let navigation = Navigation::new(Input {
+            edges,
+            closures,
+            ..Default::default()
+        });
+        let mut state = state.lock().unwrap();
+        state.graph = navigation.graph;
+        state.edges = navigation.edges;
+        state.network_version += 1;
+        Ok((
+            200,
+            Json::Object(vec![
+                (
+                    "stations".to_string(),
+                    Json::from(state.graph.station_ids.len() as i32),
+                ),
+                ("edges".to_string(), Json::from(state.edges.len() as i32)),
+            ]),
+        ))
+    }
+
+    // A navigation on a copy of the current network, with the version it was
+    // copied at.
+    fn create_navigation(
+        state: &Arc<Mutex<ServerState>>,
+        json: &Json,
+    ) -> Result<(Navigation, usize), (u16, String)> {
+        let mut input = Input::default();
+        for train in Server::get_items(json, "trains")? {
+            let name = Server::get_string(&train, "name")?;
+            input.trains.push((
+                name.clone(),
+                Server::get_non_negative_number(&train, "capacity")?,
+                Server::get_string(&train, "start")?,
+            ));
+            if train.get("available_at").is_some() {
+                input
+                    .train_departures
+                    .push((name.clone(), Server::get_number(&train, "available_at")?));
+            }
+            if train.get("fixed_cost").is_some() || train.get("cost_per_distance").is_some() {
+                input.train_costs.push((
+                    name,
+                    Server::get_optional_number(&train, "fixed_cost")?,
+                    Server::get_optional_number(&train, "cost_per_distance")?,
+                ));
+            }
+        }
+        for package in Server::get_items(json, "packages")? {
+            input.packages.push((
+                Server::get_string(&package, "name")?,
+                Server::get_non_negative_number(&package, "weight")?,
+                Server::get_string(&package, "from")?,
+                Server::get_string(&package, "to")?,
+            ));
+        }
+        for hub in Server::get_items(json, "hubs")? {
+            match hub.as_str() {
+                Some(hub) => input.hubs.push(hub.to_string()),
+                None => return Err((400, "hubs must be station names".to_string())),
+            }
+        }
+        for toll in Server::get_items(json, "tolls")? {
+            input.tolls.push((
+                Server::get_string(&toll, "edge")?,
+                Server::get_non_negative_number(&toll, "toll")?,
+            ));
+        }
+        input.objective = match json.get("objective").map(Json::as_str) {
+            None | Some(Some("makespan")) => Objective::Makespan,
+            Some(Some("cost")) => Objective::Cost,
+            Some(_) => return Err((400, "objective must be makespan or cost".to_string())),
+        };
+
+        let (graph, edges, network_version) = {
+            let state = state.lock().unwrap();
+            if state.network_version == 0 {
+                return Err((400, "Network not loaded".to_string()));
+            }
+            (
+                state.graph.clone(),
+                state.edges.clone(),
+                state.network_version,
+            )
+        };
+        for (edge_name, _) in input.tolls.iter() {
+            if !edges.contains_key(edge_name) {
+                return Err((400, format!("Edge {} not found", edge_name)));
+            }
+        }
+        // The options are read the same way as for any other input, only the
+        // graph with its warm cache comes from the server.
+        let mut navigation = Navigation::build_on(graph, edges, input);
+        navigation.refresh_distance_matrix();
+        Server::keep_cache(state, &navigation.graph, network_version);
+        Ok((navigation, network_version))
+    }
+
+    // Brings shortest path trees worked out on a copy of the network back
+    // into the server's graph.
+    fn keep_cache(state: &Arc<Mutex<ServerState>>, graph: &Graph, network_version: usize) {
+        let mut state = state.lock().unwrap();
+        if state.network_version == network_version {
+            for (source, shortest_paths) in graph.cache.iter() {
+                state
+                    .graph
+                    .cache
+                    .entry(*source)
+                    .or_insert(shortest_paths.clone());
+            }
+        }
+    }
+
+    fn submit_problem(
+        state: &Arc<Mutex<ServerState>>,
+        body: &str,
+    ) -> Result<(u16, Json), (u16, String)> {
+        let json = Server::parse_body(body)?;
+        let (mut navigation, network_version) = Server::create_navigation(state, &json)?;
+        let id = {
+            let mut state = state.lock().unwrap();
+            let id = state.problems.len() + 1;
+            state.problems.insert(id, ProblemStatus::Running);
+            id
+        };
+
+        let state = state.clone();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                navigation.solve(
+                    navigation.trains.clone(),
+                    navigation.packages.clone(),
+                    vec![],
+                )
+            }));
+            let status = match result {
+                Ok(movements) => {
+                    Server::keep_cache(&state, &navigation.graph, network_version);
+                    let summary = navigation.summarise(movements.clone());
+                    ProblemStatus::Solved(movements, summary)
+                }
+                Err(error) => ProblemStatus::Failed(
+                    match (error.downcast_ref::<&str>(), error.downcast_ref::<String>()) {
+                        (Some(message), _) => message.to_string(),
+                        (_, Some(message)) => message.clone(),
+                        _ => "Solve failed".to_string(),
+                    },
+                ),
+            };
+            state.lock().unwrap().problems.insert(id, status);
+        });
+
+        Ok((
+            202,
+            Json::Object(vec![
+                ("id".to_string(), Json::from(id as i32)),
+                ("status".to_string(), Json::from("running")),
+            ]),
+        ))
+    }
+
+    fn get_problem(
+        state: &Arc<Mutex<ServerState>>,
+        id: &str,
+    ) -> Result<(u16, Json), (u16, String)> {
+        let id: usize = match id.parse() {
+            Ok(id) => id,
+            Err(_) => return Err((404, "Problem not found".to_string())),
+        };
+        let status = match state.lock().unwrap().problems.get(&id) {
+            Some(status) => status.clone(),
+            None => return Err((404, "Problem not found".to_string())),
+        };
+        let mut entries: Vec<(String, Json)> = vec![("id".to_string(), Json::from(id as i32))];
+        match status {
+            ProblemStatus::Running => {
+                entries.push(("status".to_string(), Json::from("running")));
+            }
+            ProblemStatus::Solved(movements, summary) => {
+                entries.push(("status".to_string(), Json::from("solved")));
+                entries.push(("movements".to_string(), Json::from(movements)));
+                entries.push(("summary".to_string(), Json::from(summary)));
+            }
+            ProblemStatus::Failed(error) => {
+                entries.push(("status".to_string(), Json::from("failed")));
+                entries.push(("error".to_string(), Json::from(error)));
+            }
+        }
+        Ok((200, Json::Object(entries)))
+    }
+
+    fn validate_plan(
+        state: &Arc<Mutex<ServerState>>,
+        body: &str,
+    ) -> Result<(u16, Json), (u16, String)> {
+        let json = Server::parse_body(body)?;
+        let mut movements: Vec<Movement> = vec![];
+        for movement in Server::get_items(&json, "movements")? {
+            movements.push(Movement::from_json(&movement).map_err(|error| (400, error))?);
+        }
+        let (navigation, _) = Server::create_navigation(state, &json)?;
+        Ok((
+            200,
+            Json::Object(vec![(
+                "errors".to_string(),
+                Json::from(navigation.validate_plan(movements)),
+            )]),
+        ))
+    }
+
+    fn get_path(
+        state: &Arc<Mutex<ServerState>>,
+        query: &str,
+    ) -> Result<(u16, Json), (u16, String)> {
+        let mut parameters: HashMap<String, String> = HashMap::new();
+        for parameter in query.split('&') {
+            if let Some((key, value)) = parameter.split_once('=') {
+                parameters.insert(key.to_string(), Server::decode(value));
+            }
+        }
+        let (from, to) = match (parameters.get("from"), parameters.get("to")) {
+            (Some(from), Some(to)) => (from.clone(), to.clone()),
+            _ => return Err((400, "from and to missing".to_string())),
+        };
+
+        // Searched on a copy so other requests are not held up.
+        let (mut graph, network_version) = {
+            let state = state.lock().unwrap();
+            (state.graph.clone(), state.network_version)
+        };
+        let (from_id, to_id) = match (graph.get_station_id(&from), graph.get_station_id(&to)) {
+            (Some(from_id), Some(to_id)) => (from_id, to_id),
+            _ => return Err((404, "Station not found".to_string())),
+        };
+        let destination: Destination = match graph.get_destination_by_id(from_id, to_id) {
+            Some(destination) => destination,
+            None => return Err((404, "Destination not found".to_string())),
+        };
+        Server::keep_cache(state, &graph, network_version);
+        let mut stations: Vec<String> = vec![destination.from.clone()];
+        for checkpoint in destination.checkpoints.iter() {
+            stations.push(checkpoint.to.clone());
+        }
+        if destination.to != destination.from {
+            stations.push(destination.to.clone());
+        }
+        Ok((
+            200,
+            Json::Object(vec![
+                ("from".to_string(), Json::from(destination.from)),
+                ("to".to_string(), Json::from(destination.to)),
+                (
+                    "distance".to_string(),
+                    Json::from(destination.cumulative_distance),
+                ),
+                ("stations".to_string(), Json::from(stations)),
+            ]),
+        ))
+    }
+
+    // Percent-decoding for query values.
+    fn decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded: Vec<u8> = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => decoded.push(b' '),
+                b'%' if i + 2 < bytes.len() => {
+                    match std::str::from_utf8(&bytes[i + 1..i + 3])
+                        .ok()
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    {
+                        Some(byte) => {
+                            decoded.push(byte);
+                            i += 2;
+                        }
+                        None => decoded.push(b'%'),
+                    }
+                }
+                byte => decoded.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+        time::Duration,
+    };
+
+    use crate::json::Json;
+
+    use super::Server;
+
+    fn request(address: &str, method: &str, path: &str, body: &str) -> (u16, Json) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = "".to_string();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, Json::parse(body).unwrap())
+    }
+
+    #[test]
+    fn test_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = Server::new();
+        let state = server.state.clone();
+        thread::spawn(move || server.serve(listener));
+
+        let problem = "{\"trains\": [{\"name\": \"Q1\", \"capacity\": 6, \"start\": \"B\", \"fixed_cost\": 100}],\
+                       \"packages\": [{\"name\": \"K1\", \"weight\": 5, \"from\": \"A\", \"to\": \"C\"}]";
+        let (status, _) = request(&address, "POST", "/problems", &format!("{}}}", problem));
+        assert_eq!(status, 400);
+
+        let (status, json) = request(
+            &address,
+            "POST",
+            "/network",
+            "{\"edges\": [{\"name\": \"E1\", \"from\": \"A\", \"to\": \"B\", \"distance\": 30},\
+                          {\"name\": \"E2\", \"from\": \"B\", \"to\": \"C\", \"distance\": 10}]}",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(json.get("stations"), Some(&Json::from(3)));
+
+        let (status, json) = request(&address, "POST", "/problems", &format!("{}}}", problem));
+        assert_eq!(status, 202);
+        let id = json.get("id").and_then(Json::as_i32).unwrap();
+        let mut json = Json::Null;
+        for _ in 0..100 {
+            (_, json) = request(&address, "GET", &format!("/problems/{}", id), "");
+            if json.get("status") != Some(&Json::from("running")) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(json.get("status"), Some(&Json::from("solved")));
+        let movements = json.get("movements").unwrap().to_string();
+        assert_eq!(
+            movements,
+            "[{\"start_time\":0,\"end_time\":30,\"from\":\"B\",\"to\":\"A\",\"edge\":\"E1\",\
+             \"train\":\"Q1\",\"packages_picked_up\":[],\"packages_delivered\":[]},\
+             {\"start_time\":30,\"end_time\":60,\"from\":\"A\",\"to\":\"B\",\"edge\":\"E1\",\
+             \"train\":\"Q1\",\"packages_picked_up\":[\"K1\"],\"packages_delivered\":[]},\
+             {\"start_time\":60,\"end_time\":70,\"from\":\"B\",\"to\":\"C\",\"edge\":\"E2\",\
+             \"train\":\"Q1\",\"packages_picked_up\":[],\"packages_delivered\":[\"K1\"]}]"
+        );
+        assert_eq!(
+            json.get("summary")
+                .and_then(|summary| summary.get("makespan"))
+                .and_then(Json::as_i32),
+            Some(70)
+        );
+        assert_eq!(
+            json.get("summary")
+                .and_then(|summary| summary.get("total_cost"))
+                .and_then(Json::as_i32),
+            Some(100)
+        );
+        assert!(!state.lock().unwrap().graph.cache.is_empty());
+
+        let (status, json) = request(
+            &address,
+            "POST",
+            "/validate",
+            &format!("{}, \"movements\": {}}}", problem, movements),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(json.to_string(), "{\"errors\":[]}");
+        let (_, json) = request(
+            &address,
+            "POST",
+            "/validate",
+            &format!("{}, \"movements\": []}}", problem),
+        );
+        assert_eq!(
+            json.to_string(),
+            "{\"errors\":[{\"type\":\"package_not_delivered\",\"package\":\"K1\"}]}"
+        );
+        let transfer = "{\"trains\": [{\"name\": \"Q1\", \"capacity\": 6, \"start\": \"A\"},\
+                                    {\"name\": \"Q2\", \"capacity\": 6, \"start\": \"B\"}],\
+                        \"packages\": [{\"name\": \"K1\", \"weight\": 5, \"from\": \"A\", \"to\": \"C\"}],\
+                        \"movements\": [{\"start_time\": 0, \"end_time\": 30, \"from\": \"A\", \"to\": \"B\",\
+                                        \"train\": \"Q1\", \"packages_picked_up\": [\"K1\"],\
+                                        \"packages_delivered\": [], \"packages_transferred\": [\"K1\"]},\
+                                       {\"start_time\": 30, \"end_time\": 40, \"from\": \"B\", \"to\": \"C\",\
+                                        \"train\": \"Q2\", \"packages_picked_up\": [\"K1\"],\
+                                        \"packages_delivered\": [\"K1\"]}]";
+        let (_, json) = request(
+            &address,
+            "POST",
+            "/validate",
+            &format!("{}, \"hubs\": [\"B\"]}}", transfer),
+        );
+        assert_eq!(json.to_string(), "{\"errors\":[]}");
+        let (_, json) = request(&address, "POST", "/validate", &format!("{}}}", transfer));
+        assert!(json.to_string().contains("transfer_not_at_hub"));
+
+        let (status, json) = request(&address, "GET", "/paths?from=A&to=C", "");
+        assert_eq!(status, 200);
+        assert_eq!(
+            json.to_string(),
+            "{\"from\":\"A\",\"to\":\"C\",\"distance\":40,\"stations\":[\"A\",\"B\",\"C\"]}"
+        );
+        let (status, _) = request(&address, "GET", "/paths?from=A&to=Z", "");
+        assert_eq!(status, 404);
+        let (status, _) = request(&address, "GET", "/problems/99", "");
+        assert_eq!(status, 404);
+
+        let (status, json) = request(
+            &address,
+            "POST",
+            "/network",
+            "{\"edges\": [{\"name\": \"E1\", \"from\": \"A\", \"to\": \"B\", \"distance\": -30}]}",
+        );
+        assert_eq!(status, 400);
+        assert_eq!(
+            json.get("error"),
+            Some(&Json::from("distance must not be negative"))
+        );
+        let (status, json) = request(
+            &address,
+            "POST",
+            "/problems",
+            "{\"trains\": [{\"name\": \"Q1\", \"capacity\": 6.5, \"start\": \"B\"}]}",
+        );
+        assert_eq!(status, 400);
+        assert_eq!(
+            json.get("error"),
+            Some(&Json::from("capacity must be an integer"))
+        );
+        let (status, json) = request(
+            &address,
+            "POST",
+            "/problems",
+            "{\"packages\": [{\"name\": \"K1\", \"weight\": -5, \"from\": \"A\", \"to\": \"C\"}]}",
+        );
+        assert_eq!(status, 400);
+        assert_eq!(
+            json.get("error"),
+            Some(&Json::from("weight must not be negative"))
+        );
+        let (status, _) = request(
+            &address,
+            "POST",
+            "/problems",
+            &format!("{}{}", "[".repeat(1000), "]".repeat(1000)),
+        );
+        assert_eq!(status, 400);
+
+        let mut stream = TcpStream::connect(&address).unwrap();
+        write!(
+            stream,
+            "POST /network HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1000000000\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = "".to_string();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+    }
+}
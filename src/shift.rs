@@ -0,0 +1,319 @@
+use crate::{movement::Movement, navigation::Navigation, train::Train};
+
+impl Train {
+    pub fn has_limits(&self) -> bool {
+        self.max_operating_time.is_some()
+            || self.max_distance.is_some()
+            || self.rest_after.is_some()
+    }
+    // Whether the train has to rest before driving for `duration`. A single
+    // edge longer than the limit can still be driven right after a break.
+    pub fn needs_rest(&self, duration: i32) -> bool {
+        match self.rest_after {
+            Some(rest_after) => {
+                self.driven_since_rest > 0 && self.driven_since_rest + duration > rest_after
+            }
+            None => false,
+        }
+    }
+    // Whether the train's movements in the plan go over its operating time or
+    // distance.
+    pub fn breaks_limits(&self, movements: &[Movement]) -> bool {
+        let (operating_time, distance) = Navigation::get_train_usage(&self.name, movements);
+        self.max_operating_time
+            .is_some_and(|max_operating_time| operating_time > max_operating_time)
+            || self
+                .max_distance
+                .is_some_and(|max_distance| distance > max_distance)
+    }
+}
+
+impl Navigation {
+    // The train's first departure and last arrival, None when it does not
+    // move.
+    pub fn get_train_times(train_name: &str, movements: &[Movement]) -> Option<(i32, i32)> {
+        let mut times: Option<(i32, i32)> = None;
+        for movement in movements
+            .iter()
+            .filter(|movement| movement.train == train_name)
+        {
+            times = Some(match times {
+                Some((first_departure, last_arrival)) => (
+                    first_departure.min(movement.start_time),
+                    last_arrival.max(movement.end_time),
+                ),
+                None => (movement.start_time, movement.end_time),
+            });
+        }
+        times
+    }
+    // The time from the train's first departure to its last arrival and the
+    // distance it travels. Breaks are movements from a station to itself.
+    pub fn get_train_usage(train_name: &str, movements: &[Movement]) -> (i32, i32) {
+        let distance = movements
+            .iter()
+            .filter(|movement| movement.train == train_name && movement.from != movement.to)
+            .map(|movement| movement.end_time - movement.start_time)
+            .sum();
+        match Navigation::get_train_times(train_name, movements) {
+            Some((first_departure, last_arrival)) => (last_arrival - first_departure, distance),
+            None => (0, distance),
+        }
+    }
+
+    // Inserts the breaks the train needs before the movements from
+    // `first_new` on, which are all its own, delaying them and waiting again
+    // for closures when needed.
+    pub fn add_rest_breaks(
+        &self,
+        train: &mut Train,
+        movements: Vec<Movement>,
+        first_new: usize,
+    ) -> Vec<Movement> {
+        if train.rest_after.is_none() {
+            return movements;
+        }
+        let mut new_movements: Vec<Movement> = movements[..first_new].to_vec();
+        let mut delay = 0;
+        for movement in movements[first_new..].iter() {
+            let duration = movement.end_time - movement.start_time;
+            let mut start_time = movement.start_time + delay;
+            if train.needs_rest(duration) {
+                new_movements.push(Movement {
+                    start_time,
+                    end_time: start_time + train.rest_duration,
+                    from: movement.from.clone(),
+                    to: movement.from.clone(),
+                    edge: "".to_string(),
+                    train: train.name.clone(),
+                    packages_picked_up: vec![],
+                    packages_delivered: vec![],
+                    packages_transferred: vec![],
+                });
+                start_time += train.rest_duration;
+                train.driven_since_rest = 0;
+            }
+            if let (Some(from), Some(to)) = (
+                self.graph.get_station_id(&movement.from),
+                self.graph.get_station_id(&movement.to),
+            ) {
+                start_time = self
+                    .graph
+                    .get_departure_time(from, to, duration, start_time);
+            }
+            delay = start_time - movement.start_time;
+            train.driven_since_rest += duration;
+            new_movements.push(Movement {
+                start_time,
+                end_time: start_time + duration,
+                ..movement.clone()
+            });
+        }
+        new_movements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        input::Input, movement::Movement, navigation::Navigation, validation::ValidationError,
+    };
+
+    fn create_input() -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+                ("E3".to_string(), "C".to_string(), "D".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "D".to_string()),
+                ("K2".to_string(), 5, "A".to_string(), "B".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 10, "A".to_string()),
+                ("Q2".to_string(), 10, "A".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rest_breaks() {
+        let mut navigation = Navigation::new(Input {
+            rest_breaks: vec![("Q1".to_string(), 15, 5), ("Q2".to_string(), 15, 5)],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let legs: Vec<(String, String, i32, i32)> = movements
+            .iter()
+            .map(|movement| {
+                (
+                    movement.from.clone(),
+                    movement.to.clone(),
+                    movement.start_time,
+                    movement.end_time,
+                )
+            })
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                ("A".to_string(), "B".to_string(), 0, 10),
+                ("B".to_string(), "B".to_string(), 10, 15),
+                ("B".to_string(), "C".to_string(), 15, 25),
+                ("C".to_string(), "C".to_string(), 25, 30),
+                ("C".to_string(), "D".to_string(), 30, 40),
+            ]
+        );
+        assert_eq!(navigation.validate_plan(movements.clone()), vec![]);
+
+        let without_breaks = movements
+            .into_iter()
+            .filter(|movement| movement.from != movement.to)
+            .collect();
+        assert_eq!(
+            navigation.validate_plan(without_breaks),
+            vec![
+                ValidationError::RestBreakMissing {
+                    train: "Q1".to_string(),
+                    time: 15,
+                },
+                ValidationError::RestBreakMissing {
+                    train: "Q1".to_string(),
+                    time: 30,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distance_and_operating_time_limits() {
+        let mut navigation = Navigation::new(Input {
+            max_distances: vec![("Q1".to_string(), 20)],
+            ..create_input()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert!(movements.iter().all(|movement| movement.train == "Q2"));
+        assert_eq!(
+            Navigation::get_longest_distance_in_movements(movements.clone()),
+            30
+        );
+        assert!(navigation.stats.branches_pruned > 0);
+
+        let mut limited = Navigation::new(Input {
+            max_operating_times: vec![("Q1".to_string(), 20)],
+            max_distances: vec![("Q1".to_string(), 20)],
+            ..create_input()
+        });
+        let mut plan = movements.clone();
+        for movement in plan.iter_mut() {
+            movement.train = "Q1".to_string();
+        }
+        assert_eq!(
+            limited.validate_plan(plan),
+            vec![
+                ValidationError::OperatingTimeExceeded {
+                    train: "Q1".to_string(),
+                    operating_time: 30,
+                },
+                ValidationError::DistanceExceeded {
+                    train: "Q1".to_string(),
+                    distance: 30,
+                },
+            ]
+        );
+        assert_eq!(
+            limited.calculate(limited.trains.clone(), limited.packages.clone(), vec![]),
+            movements
+        );
+    }
+
+    #[test]
+    fn test_cache_key_with_limits() {
+        let navigation = Navigation::new(Input {
+            max_operating_times: vec![("Q1".to_string(), 30)],
+            ..create_input()
+        });
+        let movement = |train: &str, start_time: i32| Movement {
+            start_time,
+            end_time: start_time + 10,
+            from: "A".to_string(),
+            to: "B".to_string(),
+            edge: "E1".to_string(),
+            train: train.to_string(),
+            packages_picked_up: vec![],
+            packages_delivered: vec![],
+            packages_transferred: vec![],
+        };
+        let get_cache_key = |movements: Vec<Movement>| {
+            Navigation::get_cache_key(
+                navigation.trains.clone(),
+                navigation.packages.clone(),
+                &movements,
+            )
+        };
+        // Q1 has used 10 or 15 of its operating time, depending on when it
+        // left.
+        assert_ne!(
+            get_cache_key(vec![movement("Q1", 0)]),
+            get_cache_key(vec![movement("Q1", 5)])
+        );
+        // Q2 has no limits, only where it is matters.
+        assert_eq!(
+            get_cache_key(vec![movement("Q2", 0)]),
+            get_cache_key(vec![movement("Q2", 5)])
+        );
+    }
+
+    #[test]
+    fn test_try_calculate_without_solution() {
+        let mut navigation = Navigation::new(Input {
+            max_distances: vec![("Q1".to_string(), 20), ("Q2".to_string(), 20)],
+            ..create_input()
+        });
+        assert_eq!(
+            navigation.try_calculate(
+                navigation.trains.clone(),
+                navigation.packages.clone(),
+                vec![],
+            ),
+            None
+        );
+        // Dead ends are remembered so they are not searched again.
+        assert!(!navigation.cache.is_empty());
+        assert!(navigation.cache.values().all(Option::is_none));
+        let memo_misses = navigation.stats.memo_misses;
+        assert_eq!(
+            navigation.try_calculate(
+                navigation.trains.clone(),
+                navigation.packages.clone(),
+                vec![],
+            ),
+            None
+        );
+        assert_eq!(navigation.stats.memo_misses, memo_misses);
+    }
+
+    #[test]
+    #[should_panic(expected = "No solution found")]
+    fn test_limits_without_solution() {
+        let mut navigation = Navigation::new(Input {
+            max_distances: vec![("Q1".to_string(), 20), ("Q2".to_string(), 20)],
+            ..create_input()
+        });
+        navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use std::{collections::HashMap, fs, io};
+
+use crate::{
+    graph::{Graph, ShortestPaths},
+    input::Input,
+    json::Json,
+    movement::Movement,
+    navigation::Navigation,
+};
+
+// Which caches a snapshot could be used for. The shortest paths only need the
+// same network, the planned sub-problems also need the same trains and
+// packages.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapshotStatus {
+    pub graph_cache: bool,
+    pub navigation_cache: bool,
+    // Why the snapshot could not be read, "" when it could or there was none.
+    pub error: String,
+}
+
+// 64-bit FNV-1a, stable across runs and platforms unlike `DefaultHasher`.
+pub fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+impl Graph {
+    // Covers everything the shortest path trees depend on, including the
+    // station IDs they are indexed by.
+    pub fn get_network_hash(&self) -> String {
+        let mut closures: Vec<String> = self
+            .closures
+            .iter()
+            .map(|(edge, windows)| format!("{:?}:{:?}", edge, windows))
+            .collect();
+        closures.sort();
+        format!(
+            "{:016x}",
+            fnv1a(&format!(
+                "{:?};{:?};{:?};{:?}",
+                self.stations, self.edges, closures, self.tie_break
+            ))
+        )
+    }
+    pub fn cache_to_json(&self) -> Json {
+        let mut sources: Vec<usize> = self.cache.keys().copied().collect();
+        sources.sort();
+        let mut trees: Vec<Json> = vec![];
+        for source in sources {
+            let shortest_paths = &self.cache[&source];
+            let distances: Vec<Option<i32>> = shortest_paths
+                .distances
+                .iter()
+                .map(|distance| Some(*distance).filter(|distance| *distance != i32::MAX))
+                .collect();
+            let previous: Vec<Option<i32>> = shortest_paths
+                .previous
+                .iter()
+                .map(|previous| previous.map(|previous| previous as i32))
+                .collect();
+            trees.push(Json::Object(vec![
+                ("source".to_string(), Json::from(source as i32)),
+                ("distances".to_string(), Json::from(distances)),
+                ("previous".to_string(), Json::from(previous)),
+            ]));
+        }
+        Json::Array(trees)
+    }
+    pub fn load_cache(&mut self, json: &Json) -> Result<(), String> {
+        let mut cache: HashMap<usize, ShortestPaths> = HashMap::new();
+        for tree in json.as_array().ok_or("Graph cache must be a list")? {
+            let source = tree
+                .get("source")
+                .and_then(Json::as_i32)
+                .ok_or("Tree source missing")? as usize;
+            let distances = tree
+                .get("distances")
+                .and_then(Json::as_array)
+                .ok_or("Tree distances missing")?;
+            let previous = tree
+                .get("previous")
+                .and_then(Json::as_array)
+                .ok_or("Tree previous missing")?;
+            if source >= self.stations.len()
+                || distances.len() != self.stations.len()
+                || previous.len() != self.stations.len()
+            {
+                return Err("Tree does not match the network".to_string());
+            }
+            let shortest_paths = ShortestPaths {
+                distances: distances
+                    .iter()
+                    .map(|distance| distance.as_i32().unwrap_or(i32::MAX))
+                    .collect(),
+                previous: previous
+                    .iter()
+                    .map(|previous| previous.as_i32().map(|previous| previous as usize))
+                    .collect(),
+            };
+            if !self.is_tree_of(source, &shortest_paths) {
+                return Err("Tree does not match the network".to_string());
+            }
+            cache.insert(source, shortest_paths);
+        }
+        self.cache.extend(cache);
+        Ok(())
+    }
+    // Whether every previous station is a neighbour the distance was reached
+    // over, and following them always leads back to the source, so rebuilding
+    // a path can neither index out of range nor go round in circles.
+    fn is_tree_of(&mut self, source: usize, shortest_paths: &ShortestPaths) -> bool {
+        self.build_adjacency();
+        let (distances, previous) = (&shortest_paths.distances, &shortest_paths.previous);
+        if distances[source] != 0 || previous[source].is_some() {
+            return false;
+        }
+        for station in 0..self.stations.len() {
+            let is_consistent = match previous[station] {
+                Some(prev) => {
+                    prev < self.stations.len()
+                        && distances[prev] != i32::MAX
+                        && self.get_neighbours(prev).any(|(next, distance)| {
+                            next == station && distances[prev] + distance == distances[station]
+                        })
+                }
+                None => station == source || distances[station] == i32::MAX,
+            };
+            if !is_consistent {
+                return false;
+            }
+        }
+        // Edges of distance 0 could still make a cycle.
+        let mut leads_to_source = vec![false; self.stations.len()];
+        leads_to_source[source] = true;
+        for station in 0..self.stations.len() {
+            let mut path: Vec<usize> = vec![];
+            let mut current = station;
+            while !leads_to_source[current] {
+                match previous[current] {
+                    Some(prev) if path.len() < self.stations.len() => {
+                        path.push(current);
+                        current = prev;
+                    }
+                    Some(_) => return false,
+                    None => break,
+                }
+            }
+            if leads_to_source[current] {
+                for station in path {
+                    leads_to_source[station] = true;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Navigation {
+    // Covers the starting trains and packages and the options that change
+    // which plan is picked.
+    pub fn get_problem_hash(&self) -> String {
+        let mut trains: Vec<String> = self
+            .trains
+            .values()
+            .map(|train| format!("{:?}", train))
+            .collect();
+        trains.sort();
+        let mut packages: Vec<String> = self
+            .packages
+            .values()
+            .map(|package| format!("{:?}", package))
+            .collect();
+        packages.sort();
+        let mut hubs = self.hubs.clone();
+        hubs.sort();
+        let mut tolls: Vec<(&String, &i32)> = self.tolls.iter().collect();
+        tolls.sort();
+        format!(
+            "{:016x}",
+            fnv1a(&format!(
+                "{:?};{:?};{};{:?};{:?};{:?};{:?}",
+                trains,
+                packages,
+                self.route_alternatives,
+                hubs,
+                self.dimensions,
+                tolls,
+                self.objective
+            ))
+        )
+    }
+    pub fn save_snapshot(&self) -> String {
+        let mut keys: Vec<&String> = self.cache.keys().collect();
+        keys.sort();
+        let mut plans: Vec<Json> = vec![];
+        for key in keys {
+            plans.push(Json::Object(vec![
+                ("key".to_string(), Json::from(key.clone())),
+                ("movements".to_string(), Json::from(self.cache[key].clone())),
+            ]));
+        }
+        Json::Object(vec![
+            (
+                "network_hash".to_string(),
+                Json::from(self.graph.get_network_hash()),
+            ),
+            (
+                "problem_hash".to_string(),
+                Json::from(self.get_problem_hash()),
+            ),
+            ("graph_cache".to_string(), self.graph.cache_to_json()),
+            ("navigation_cache".to_string(), Json::Array(plans)),
+        ])
+        .to_string()
+    }
+    pub fn save_snapshot_file(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.save_snapshot())
+    }
+    // Restores the caches the snapshot is still valid for and leaves the rest
+    // as they are. A snapshot that cannot be read is an error and changes
+    // nothing.
+    pub fn load_snapshot(&mut self, snapshot: &str) -> Result<SnapshotStatus, String> {
+        let json = Json::parse(snapshot)?;
+        let mut status = SnapshotStatus::default();
+        if json.get("network_hash").and_then(Json::as_str) != Some(&self.graph.get_network_hash()) {
+            return Ok(status);
+        }
+
+        let mut cache: HashMap<String, Option<Vec<Movement>>> = HashMap::new();
+        let same_problem =
+            json.get("problem_hash").and_then(Json::as_str) == Some(&self.get_problem_hash());
+        if same_problem {
+            for plan in json
+                .get("navigation_cache")
+                .and_then(Json::as_array)
+                .ok_or("Navigation cache missing")?
+            {
+                let key = plan
+                    .get("key")
+                    .and_then(Json::as_str)
+                    .ok_or("Plan key missing")?;
+                // States without a plan are saved as null.
+                let movements = match plan.get("movements").ok_or("Plan movements missing")? {
+                    Json::Null => None,
+                    movements => {
+                        let mut plan_movements: Vec<Movement> = vec![];
+                        for movement in movements.as_array().ok_or("Plan movements missing")? {
+                            plan_movements.push(Movement::from_json(movement)?);
+                        }
+                        Some(plan_movements)
+                    }
+                };
+                cache.insert(key.to_string(), movements);
+            }
+        }
+
+        self.graph
+            .load_cache(json.get("graph_cache").ok_or("Graph cache missing")?)?;
+        status.graph_cache = true;
+        if same_problem {
+            self.cache.extend(cache);
+            status.navigation_cache = true;
+        }
+        Ok(status)
+    }
+    // Like `new`, but loads the snapshot before working out the distance
+    // matrix so it can come from the saved shortest paths. A missing file is
+    // the same as no snapshot, one that cannot be read is only used for its
+    // error.
+    pub fn new_with_snapshot_file(input: Input, path: &str) -> (Navigation, SnapshotStatus) {
+        let mut navigation = Navigation::build(input);
+        let status = match fs::read_to_string(path) {
+            Ok(snapshot) => match navigation.load_snapshot(&snapshot) {
+                Ok(status) => status,
+                Err(error) => SnapshotStatus {
+                    error,
+                    ..Default::default()
+                },
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => SnapshotStatus::default(),
+            Err(error) => SnapshotStatus {
+                error: error.to_string(),
+                ..Default::default()
+            },
+        };
+        navigation.refresh_distance_matrix();
+        (navigation, status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::{cost::Objective, input::Input, navigation::Navigation};
+
+    use super::SnapshotStatus;
+
+    fn create_input(distance: i32, weight: i32) -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), distance),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), weight, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "B".to_string(), "A".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 6, "B".to_string()),
+                ("Q2".to_string(), 6, "C".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut navigation = Navigation::new(create_input(30, 5));
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let path = env::temp_dir().join(format!("trains-snapshot-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        navigation.save_snapshot_file(path).unwrap();
+
+        let (mut loaded, status) = Navigation::new_with_snapshot_file(create_input(30, 5), path);
+        assert_eq!(
+            status,
+            SnapshotStatus {
+                graph_cache: true,
+                navigation_cache: true,
+                error: "".to_string(),
+            }
+        );
+        assert_eq!(loaded.graph.cache, navigation.graph.cache);
+        assert_eq!(loaded.cache, navigation.cache);
+        assert_eq!(
+            loaded.calculate(loaded.trains.clone(), loaded.packages.clone(), vec![]),
+            movements
+        );
+
+        let (loaded, status) = Navigation::new_with_snapshot_file(create_input(30, 4), path);
+        assert_eq!(
+            status,
+            SnapshotStatus {
+                graph_cache: true,
+                navigation_cache: false,
+                error: "".to_string(),
+            }
+        );
+        assert!(loaded.cache.is_empty());
+
+        let (loaded, status) = Navigation::new_with_snapshot_file(
+            Input {
+                hubs: vec!["B".to_string()],
+                ..create_input(30, 5)
+            },
+            path,
+        );
+        assert_eq!(
+            status,
+            SnapshotStatus {
+                graph_cache: true,
+                navigation_cache: false,
+                error: "".to_string(),
+            }
+        );
+        assert!(loaded.cache.is_empty());
+
+        let (loaded, status) = Navigation::new_with_snapshot_file(
+            Input {
+                tolls: vec![("E2".to_string(), 10)],
+                objective: Objective::Cost,
+                ..create_input(30, 5)
+            },
+            path,
+        );
+        assert!(!status.navigation_cache);
+        assert!(loaded.cache.is_empty());
+
+        let (loaded, status) = Navigation::new_with_snapshot_file(create_input(20, 5), path);
+        assert_eq!(status, SnapshotStatus::default());
+        assert_eq!(loaded.distance_matrix.get_distance("A", "C"), Some(30));
+
+        // A tree pointing at a station that does not exist, and one going
+        // round in circles.
+        let network_hash = navigation.graph.get_network_hash();
+        for previous in ["[null, 7, 0]", "[null, 2, 1]"] {
+            fs::write(
+                path,
+                format!(
+                    "{{\"network_hash\": \"{}\", \"problem_hash\": \"\", \
+                     \"graph_cache\": [{{\"source\": 0, \"distances\": [0, 30, 40], \
+                     \"previous\": {}}}], \"navigation_cache\": []}}",
+                    network_hash, previous
+                ),
+            )
+            .unwrap();
+            let (loaded, status) = Navigation::new_with_snapshot_file(create_input(30, 5), path);
+            assert_eq!(status.error, "Tree does not match the network");
+            assert!(!status.graph_cache);
+            assert_eq!(loaded.distance_matrix.get_distance("A", "C"), Some(40));
+        }
+        fs::remove_file(path).unwrap();
+        let mut navigation = Navigation::new(create_input(30, 5));
+        assert!(navigation.load_snapshot("{\"network_hash\": ").is_err());
+    }
+}
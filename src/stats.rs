@@ -0,0 +1,120 @@
+pub type ProgressCallback = Box<dyn FnMut(&SolverStats) + Send>;
+
+// Counters collected by `calculate`, added up over every call until they are
+// reset.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    // Every call to `calculate`, including those answered from the memo.
+    pub states_explored: usize,
+    pub memo_hits: usize,
+    pub memo_misses: usize,
+    // (train, package, pick up) jobs tried, and those whose plan was not
+    // better than one already found.
+    pub branches_explored: usize,
+    pub branches_discarded: usize,
+    // Jobs never tried because the train could not carry the package, and
+    // those whose plan went over a train's limits.
+    pub branches_pruned: usize,
+    pub max_depth: usize,
+    // Paths that had to be searched for, and those read from the distance
+    // matrix instead.
+    pub dijkstra_calls: usize,
+    pub dijkstra_cache_hits: usize,
+    // The best complete plan found so far, ranked the way `calculate` ranks
+    // plans. The cost is only worked out when it is the objective.
+    pub best_cost: Option<i32>,
+    pub best_makespan: Option<i32>,
+    pub best_number_of_trains: Option<i32>,
+}
+
+impl SolverStats {
+    // Adds the stats of a sub-problem solved on its own. Sub-problems share
+    // no trains, so their best plans put together take as long as the
+    // longest one and use the trains of all of them.
+    pub fn add_sub_problem(&mut self, other: &SolverStats) {
+        self.states_explored += other.states_explored;
+        self.memo_hits += other.memo_hits;
+        self.memo_misses += other.memo_misses;
+        self.branches_explored += other.branches_explored;
+        self.branches_discarded += other.branches_discarded;
+        self.branches_pruned += other.branches_pruned;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.dijkstra_calls += other.dijkstra_calls;
+        self.dijkstra_cache_hits += other.dijkstra_cache_hits;
+        let add = |a: Option<i32>, b: Option<i32>| match (a, b) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.best_cost = add(self.best_cost, other.best_cost);
+        self.best_makespan = self.best_makespan.max(other.best_makespan);
+        self.best_number_of_trains = add(self.best_number_of_trains, other.best_number_of_trains);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{input::Input, navigation::Navigation};
+
+    use super::SolverStats;
+
+    #[test]
+    fn test_solver_stats() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "B".to_string(), "A".to_string()),
+            ],
+            trains: vec![
+                ("Q1".to_string(), 6, "B".to_string()),
+                ("Q2".to_string(), 20, "C".to_string()),
+            ],
+            ..Default::default()
+        });
+        let reports: Arc<Mutex<Vec<SolverStats>>> = Arc::new(Mutex::new(vec![]));
+        let progress_reports = reports.clone();
+        navigation.progress = Some(Box::new(move |stats: &SolverStats| {
+            progress_reports.lock().unwrap().push(stats.clone());
+        }));
+        navigation.progress_interval = 10;
+
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let stats = navigation.stats.clone();
+        assert_eq!(stats.states_explored, stats.memo_hits + stats.memo_misses);
+        assert_eq!(stats.states_explored, stats.branches_explored + 1);
+        assert!(stats.memo_hits > 0);
+        // The root and, for each package, picking it up and delivering it.
+        assert_eq!(stats.max_depth, 5);
+        // Q1 cannot take a package while it carries the other.
+        assert_eq!(stats.branches_pruned, 2);
+        assert_eq!(stats.dijkstra_calls, 0);
+        assert!(stats.dijkstra_cache_hits > 0);
+        // Only kept when the objective is the cost.
+        assert_eq!(stats.best_cost, None);
+        assert_eq!(
+            stats.best_makespan,
+            Some(Navigation::get_longest_distance_in_movements(
+                movements.clone()
+            ))
+        );
+        assert_eq!(
+            stats.best_number_of_trains,
+            Some(Navigation::get_number_of_trains(movements))
+        );
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), stats.states_explored / 10);
+        for report in reports.iter() {
+            assert!(report.best_makespan.is_none() || report.best_makespan >= stats.best_makespan);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::graph::Graph;
+
+// How to choose between paths of the same length. Whatever is left equal is
+// settled by comparing station names along the paths, so the choice never
+// depends on the order edges were added in.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TieBreak {
+    #[default]
+    FewestHops,
+    Lexicographic,
+    // Fewest edges outside this list of (from, to) station pairs, then fewest
+    // hops.
+    PreferredEdges(Vec<(String, String)>),
+}
+
+impl Graph {
+    // The paths to `a` and `b` from the last station they share, found by
+    // walking back from both a station at a time until one reaches a station
+    // the other has passed. Their shared start is equal in every respect
+    // paths are compared on, so only the rest needs looking at.
+    pub fn get_diverging_paths(
+        a: usize,
+        b: usize,
+        previous: &dyn Fn(usize) -> Option<usize>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let (mut path_a, mut path_b) = (vec![a], vec![b]);
+        let mut seen_a: HashMap<usize, usize> = HashMap::from([(a, 0)]);
+        let mut seen_b: HashMap<usize, usize> = HashMap::from([(b, 0)]);
+        loop {
+            if let Some(i) = seen_a.get(&path_b[path_b.len() - 1]) {
+                path_a.truncate(i + 1);
+                break;
+            }
+            if let Some(i) = seen_b.get(&path_a[path_a.len() - 1]) {
+                path_b.truncate(i + 1);
+                break;
+            }
+            let (prev_a, prev_b) = (
+                previous(path_a[path_a.len() - 1]),
+                previous(path_b[path_b.len() - 1]),
+            );
+            if prev_a.is_none() && prev_b.is_none() {
+                break;
+            }
+            if let Some(prev_a) = prev_a {
+                seen_a.insert(prev_a, path_a.len());
+                path_a.push(prev_a);
+            }
+            if let Some(prev_b) = prev_b {
+                seen_b.insert(prev_b, path_b.len());
+                path_b.push(prev_b);
+            }
+        }
+        path_a.reverse();
+        path_b.reverse();
+        (path_a, path_b)
+    }
+    fn is_preferred_edge(&self, from: usize, to: usize) -> bool {
+        match &self.tie_break {
+            TieBreak::PreferredEdges(edges) => edges.iter().any(|(a, b)| {
+                let (a, b) = (self.get_station_id(a), self.get_station_id(b));
+                (a == Some(from) && b == Some(to)) || (a == Some(to) && b == Some(from))
+            }),
+            _ => false,
+        }
+    }
+    pub fn compare_paths(&self, path_a: &[usize], path_b: &[usize]) -> Ordering {
+        let names_a: Vec<&String> = path_a.iter().map(|id| &self.stations[*id]).collect();
+        let names_b: Vec<&String> = path_b.iter().map(|id| &self.stations[*id]).collect();
+        let lexicographic = names_a.cmp(&names_b);
+        let hops = path_a.len().cmp(&path_b.len());
+        match &self.tie_break {
+            TieBreak::FewestHops => hops.then(lexicographic),
+            TieBreak::Lexicographic => lexicographic,
+            TieBreak::PreferredEdges(_) => {
+                let count_other_edges = |path: &[usize]| {
+                    path.windows(2)
+                        .filter(|leg| !self.is_preferred_edge(leg[0], leg[1]))
+                        .count()
+                };
+                count_other_edges(path_a)
+                    .cmp(&count_other_edges(path_b))
+                    .then(hops)
+                    .then(lexicographic)
+            }
+        }
+    }
+    // Whether reaching `next` through `candidate` beats its current previous
+    // station, for two paths of the same length.
+    pub fn is_better_tie(
+        &self,
+        next: usize,
+        candidate: usize,
+        previous: &dyn Fn(usize) -> Option<usize>,
+    ) -> bool {
+        let current = match previous(next) {
+            Some(current) => current,
+            None => return false,
+        };
+        if current == candidate {
+            return false;
+        }
+        let (mut candidate_path, mut current_path) =
+            Graph::get_diverging_paths(candidate, current, previous);
+        candidate_path.push(next);
+        current_path.push(next);
+        self.compare_paths(&candidate_path, &current_path) == Ordering::Less
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Coordinates, graph::Graph};
+
+    use super::TieBreak;
+
+    // With `positions` the straight line is exact along A, B, C, D, so A*
+    // has every station at the same estimate and may reach D through Z
+    // before C is expanded.
+    fn create_graph(reverse: bool, positions: bool) -> Graph {
+        let mut edges = vec![
+            ("A", "Z", 10),
+            ("Z", "D", 10),
+            ("A", "B", 5),
+            ("B", "C", 5),
+            ("C", "D", 10),
+        ];
+        if reverse {
+            edges.reverse();
+        }
+        let mut graph = Graph::new();
+        for (from, to, distance) in edges {
+            graph.add_edge(from.to_string(), to.to_string(), distance);
+        }
+        if positions {
+            for (station, x) in [
+                ("A", 0.0),
+                ("B", 5.0),
+                ("C", 10.0),
+                ("Z", 10.0),
+                ("D", 20.0),
+            ] {
+                graph.set_coordinates(station.to_string(), Coordinates::Planar { x, y: 0.0 });
+            }
+        }
+        graph
+    }
+
+    fn get_checkpoints(graph: &mut Graph) -> Vec<String> {
+        let destination = graph.get_destination("A".to_string(), "D".to_string());
+        assert_eq!(destination.cumulative_distance, 20);
+        let mut cached = graph.dijkstra("A".to_string());
+        assert_eq!(cached.remove("D"), Some(destination.clone()));
+        destination
+            .checkpoints
+            .into_iter()
+            .map(|checkpoint| checkpoint.to)
+            .collect()
+    }
+
+    #[test]
+    fn test_tie_break() {
+        for (reverse, positions) in [(false, false), (true, false), (false, true), (true, true)] {
+            let mut graph = create_graph(reverse, positions);
+            assert_eq!(get_checkpoints(&mut graph), vec!["Z".to_string()]);
+
+            let mut graph = create_graph(reverse, positions);
+            graph.tie_break = TieBreak::Lexicographic;
+            assert_eq!(
+                get_checkpoints(&mut graph),
+                vec!["B".to_string(), "C".to_string()]
+            );
+
+            let mut graph = create_graph(reverse, positions);
+            graph.tie_break = TieBreak::PreferredEdges(vec![
+                ("B".to_string(), "A".to_string()),
+                ("B".to_string(), "C".to_string()),
+                ("C".to_string(), "D".to_string()),
+            ]);
+            assert_eq!(
+                get_checkpoints(&mut graph),
+                vec!["B".to_string(), "C".to_string()]
+            );
+        }
+    }
+}
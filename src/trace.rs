@@ -0,0 +1,200 @@
+use crate::{json::Json, navigation::Navigation};
+
+// One (train, package, pick up, hub) job tried by `calculate`, with the plan it led
+// to and how that compared to the best plan of its state at the time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEntry {
+    pub depth: usize,
+    // Cache key of the state the job was tried from.
+    pub state: String,
+    pub train: String,
+    pub package: String,
+    pub to_pick_up: bool,
+    // The hub the package is left at, "" when it is not.
+    pub transfer_at: String,
+    // 0 unless the objective is the cost.
+    pub cost: i32,
+    pub makespan: i32,
+    pub number_of_trains: i32,
+    // Whether this job ended up in the state's plan.
+    pub kept: bool,
+    pub reason: String,
+}
+
+impl Navigation {
+    // Records a (train, package, pick up, hub) job tried at (depth, state) and
+    // the (cost, makespan, number of trains) its plan is ranked by when
+    // tracing is on, and returns its index in the trace.
+    pub fn trace_decision(
+        &mut self,
+        (depth, state): (usize, &str),
+        job: (&str, &str, bool, &str),
+        result: (i32, i32, i32),
+        best: (i32, i32, i32),
+    ) -> Option<usize> {
+        let trace = self.trace.as_mut()?;
+        let (train, package, to_pick_up, transfer_at) = job;
+        let (cost, makespan, number_of_trains) = result;
+        let (best_cost, best_makespan, best_number_of_trains) = best;
+        let reason = if best_makespan == i32::MAX {
+            "first job tried".to_string()
+        } else if cost < best_cost {
+            format!("lower cost than {}", best_cost)
+        } else if cost > best_cost {
+            format!("higher cost than {}", best_cost)
+        } else if makespan < best_makespan {
+            format!("shorter makespan than {}", best_makespan)
+        } else if makespan > best_makespan {
+            format!("longer makespan than {}", best_makespan)
+        } else if number_of_trains < best_number_of_trains {
+            format!("same makespan, fewer trains than {}", best_number_of_trains)
+        } else {
+            format!(
+                "same makespan, not fewer trains than {}",
+                best_number_of_trains
+            )
+        };
+        trace.push(TraceEntry {
+            depth,
+            state: state.to_string(),
+            train: train.to_string(),
+            package: package.to_string(),
+            to_pick_up,
+            transfer_at: transfer_at.to_string(),
+            cost,
+            makespan,
+            number_of_trains,
+            kept: false,
+            reason,
+        });
+        Some(trace.len() - 1)
+    }
+    pub fn trace_to_json(&self) -> String {
+        let mut entries: Vec<Json> = vec![];
+        for entry in self.trace.clone().unwrap_or_default() {
+            entries.push(Json::Object(vec![
+                ("depth".to_string(), Json::from(entry.depth as i32)),
+                ("state".to_string(), Json::from(entry.state)),
+                ("train".to_string(), Json::from(entry.train)),
+                ("package".to_string(), Json::from(entry.package)),
+                ("to_pick_up".to_string(), Json::from(entry.to_pick_up)),
+                ("transfer_at".to_string(), Json::from(entry.transfer_at)),
+                ("cost".to_string(), Json::from(entry.cost)),
+                ("makespan".to_string(), Json::from(entry.makespan)),
+                (
+                    "number_of_trains".to_string(),
+                    Json::from(entry.number_of_trains),
+                ),
+                ("kept".to_string(), Json::from(entry.kept)),
+                ("reason".to_string(), Json::from(entry.reason)),
+            ]));
+        }
+        Json::Array(entries).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cost::Objective, input::Input, json::Json, navigation::Navigation};
+
+    fn create_input() -> Input {
+        Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![
+                ("Q1".to_string(), 6, "B".to_string()),
+                ("Q2".to_string(), 6, "C".to_string()),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut navigation = Navigation::new(create_input());
+        navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(navigation.trace, None);
+
+        navigation.cache.clear();
+        navigation.trace = Some(vec![]);
+        navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let trace = navigation.trace.clone().unwrap();
+        let root: Vec<(String, i32, bool, String)> = trace
+            .iter()
+            .filter(|entry| entry.depth == 1)
+            .map(|entry| {
+                (
+                    entry.train.clone(),
+                    entry.makespan,
+                    entry.kept,
+                    entry.reason.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            root,
+            vec![
+                ("Q1".to_string(), 70, true, "first job tried".to_string()),
+                (
+                    "Q2".to_string(),
+                    80,
+                    false,
+                    "longer makespan than 70".to_string()
+                ),
+            ]
+        );
+        assert!(trace.iter().all(|entry| entry.depth >= 1));
+
+        let json = Json::parse(&navigation.trace_to_json()).unwrap();
+        assert_eq!(
+            json.as_array().map(|entries| entries.len()),
+            Some(trace.len())
+        );
+
+        // Q2 is slower but costs nothing to use.
+        let mut navigation = Navigation::new(Input {
+            train_costs: vec![("Q1".to_string(), 100, 0)],
+            objective: Objective::Cost,
+            ..create_input()
+        });
+        navigation.trace = Some(vec![]);
+        navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        let root: Vec<(String, i32, bool, String)> = navigation
+            .trace
+            .clone()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.depth == 1)
+            .map(|entry| {
+                (
+                    entry.train.clone(),
+                    entry.cost,
+                    entry.kept,
+                    entry.reason.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            root,
+            vec![
+                ("Q1".to_string(), 100, false, "first job tried".to_string()),
+                ("Q2".to_string(), 0, true, "lower cost than 100".to_string()),
+            ]
+        );
+    }
+}
@@ -3,9 +3,28 @@
 pub struct Train {
     pub name: String,
     pub start: String,
+    // The depot the train has to finish at, "" for anywhere.
+    pub end: String,
     pub current_location: String,
     pub capacity: i32,
+    // Capacities in the dimensions beyond weight, such as volume, in the
+    // order of `Navigation::dimensions`.
+    pub capacities: Vec<i32>,
+    // What the train's cars support, such as "refrigerated".
+    pub attributes: Vec<String>,
     pub total_distance: i32,
+    // Paid once if the train is used, and for every unit of distance.
+    pub fixed_cost: i32,
+    pub cost_per_distance: i32,
+    // Limits for the train's shift, None for no limit.
+    pub max_operating_time: Option<i32>,
+    pub max_distance: Option<i32>,
+    // Driving time after which the train has to stop for `rest_duration`.
+    pub rest_after: Option<i32>,
+    pub rest_duration: i32,
+    pub driven_since_rest: i32,
+    // The earliest the train can leave.
+    pub available_at: i32,
     pub packages_to_pick_up: Vec<String>,
     pub packages_picked_up: Vec<String>,
     pub packages_delivered: Vec<String>,
@@ -16,9 +35,20 @@ impl Train {
         Train {
             name: name.clone(),
             start: start.clone(),
+            end: "".to_string(),
             current_location: start.clone(),
             capacity,
+            capacities: [].to_vec(),
+            attributes: [].to_vec(),
             total_distance: 0,
+            fixed_cost: 0,
+            cost_per_distance: 0,
+            max_operating_time: None,
+            max_distance: None,
+            rest_after: None,
+            rest_duration: 0,
+            driven_since_rest: 0,
+            available_at: 0,
             packages_delivered: [].to_vec(),
             packages_to_pick_up: [].to_vec(),
             packages_picked_up: [].to_vec(),
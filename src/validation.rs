@@ -0,0 +1,905 @@
+use std::collections::HashMap;
+
+use crate::{json::Json, movement::Movement, navigation::Navigation, package::Package};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    // The edge is shorter than the straight line between its stations, which
+    // means the coordinates or the distance are wrong.
+    EdgeShorterThanStraightLine {
+        edge: String,
+        distance: i32,
+        straight_line_distance: f64,
+    },
+    // The package's origin and destination are not connected.
+    PackageUnreachable {
+        package: String,
+    },
+    // No train starts in the part of the network the package is in.
+    NoTrainCanReachPackage {
+        package: String,
+    },
+    // No train is big enough to carry the package.
+    PackageTooHeavy {
+        package: String,
+        weight: i32,
+    },
+    // No train has room for the package in one of the dimensions beyond
+    // weight.
+    PackageTooLarge {
+        package: String,
+    },
+    // Every train big enough for the package is forbidden from carrying it.
+    NoAllowedTrain {
+        package: String,
+    },
+    // No train the package is allowed on supports everything it requires.
+    NoSupportingTrain {
+        package: String,
+    },
+    // Plan errors, the time is the start of the movement they are found in.
+    UnknownTrain {
+        train: String,
+    },
+    UnknownPackage {
+        package: String,
+    },
+    // There is no edge between the stations as long as the movement takes.
+    MovementNotOnEdge {
+        train: String,
+        from: String,
+        to: String,
+        time: i32,
+    },
+    MovementDuringClosure {
+        train: String,
+        from: String,
+        to: String,
+        time: i32,
+    },
+    TrainNotAtStation {
+        train: String,
+        station: String,
+        time: i32,
+    },
+    MovementsOverlap {
+        train: String,
+        time: i32,
+    },
+    // The train's first movement is before it can leave.
+    TrainNotAvailable {
+        train: String,
+        time: i32,
+    },
+    PackageNotAtStation {
+        package: String,
+        station: String,
+        time: i32,
+    },
+    PackageNotOnTrain {
+        package: String,
+        train: String,
+        time: i32,
+    },
+    // The package is pinned to another train or forbidden from this one.
+    ForbiddenAssignment {
+        package: String,
+        train: String,
+        time: i32,
+    },
+    // The train lacks an attribute the package requires.
+    TrainMissingAttributes {
+        package: String,
+        train: String,
+        time: i32,
+    },
+    // The package was picked up while an incompatible one was on the train.
+    IncompatiblePackages {
+        package: String,
+        other_package: String,
+        train: String,
+        time: i32,
+    },
+    PackageDeliveredToWrongStation {
+        package: String,
+        station: String,
+        time: i32,
+    },
+    TransferNotAtHub {
+        package: String,
+        station: String,
+        time: i32,
+    },
+    TrainOverCapacity {
+        train: String,
+        load: i32,
+        time: i32,
+    },
+    DimensionOverCapacity {
+        train: String,
+        dimension: String,
+        load: i32,
+        time: i32,
+    },
+    PackageNotDelivered {
+        package: String,
+    },
+    // The train moved but did not finish at its end station.
+    TrainNotAtEnd {
+        train: String,
+        station: String,
+    },
+    // The train drove on without the break it needed.
+    RestBreakMissing {
+        train: String,
+        time: i32,
+    },
+    OperatingTimeExceeded {
+        train: String,
+        operating_time: i32,
+    },
+    DistanceExceeded {
+        train: String,
+        distance: i32,
+    },
+}
+
+impl From<ValidationError> for Json {
+    fn from(error: ValidationError) -> Json {
+        let (kind, fields): (&str, Vec<(&str, Json)>) = match error {
+            ValidationError::EdgeShorterThanStraightLine {
+                edge,
+                distance,
+                straight_line_distance,
+            } => (
+                "edge_shorter_than_straight_line",
+                vec![
+                    ("edge", Json::from(edge)),
+                    ("distance", Json::from(distance)),
+                    (
+                        "straight_line_distance",
+                        Json::Number(straight_line_distance),
+                    ),
+                ],
+            ),
+            ValidationError::PackageUnreachable { package } => (
+                "package_unreachable",
+                vec![("package", Json::from(package))],
+            ),
+            ValidationError::NoTrainCanReachPackage { package } => (
+                "no_train_can_reach_package",
+                vec![("package", Json::from(package))],
+            ),
+            ValidationError::PackageTooHeavy { package, weight } => (
+                "package_too_heavy",
+                vec![
+                    ("package", Json::from(package)),
+                    ("weight", Json::from(weight)),
+                ],
+            ),
+            ValidationError::NoAllowedTrain { package } => {
+                ("no_allowed_train", vec![("package", Json::from(package))])
+            }
+            ValidationError::PackageTooLarge { package } => {
+                ("package_too_large", vec![("package", Json::from(package))])
+            }
+            ValidationError::NoSupportingTrain { package } => (
+                "no_supporting_train",
+                vec![("package", Json::from(package))],
+            ),
+            ValidationError::UnknownTrain { train } => {
+                ("unknown_train", vec![("train", Json::from(train))])
+            }
+            ValidationError::UnknownPackage { package } => {
+                ("unknown_package", vec![("package", Json::from(package))])
+            }
+            ValidationError::MovementNotOnEdge {
+                train,
+                from,
+                to,
+                time,
+            } => (
+                "movement_not_on_edge",
+                vec![
+                    ("train", Json::from(train)),
+                    ("from", Json::from(from)),
+                    ("to", Json::from(to)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::MovementDuringClosure {
+                train,
+                from,
+                to,
+                time,
+            } => (
+                "movement_during_closure",
+                vec![
+                    ("train", Json::from(train)),
+                    ("from", Json::from(from)),
+                    ("to", Json::from(to)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::TrainNotAtStation {
+                train,
+                station,
+                time,
+            } => (
+                "train_not_at_station",
+                vec![
+                    ("train", Json::from(train)),
+                    ("station", Json::from(station)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::MovementsOverlap { train, time } => (
+                "movements_overlap",
+                vec![("train", Json::from(train)), ("time", Json::from(time))],
+            ),
+            ValidationError::TrainNotAvailable { train, time } => (
+                "train_not_available",
+                vec![("train", Json::from(train)), ("time", Json::from(time))],
+            ),
+            ValidationError::PackageNotAtStation {
+                package,
+                station,
+                time,
+            } => (
+                "package_not_at_station",
+                vec![
+                    ("package", Json::from(package)),
+                    ("station", Json::from(station)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::PackageNotOnTrain {
+                package,
+                train,
+                time,
+            } => (
+                "package_not_on_train",
+                vec![
+                    ("package", Json::from(package)),
+                    ("train", Json::from(train)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::ForbiddenAssignment {
+                package,
+                train,
+                time,
+            } => (
+                "forbidden_assignment",
+                vec![
+                    ("package", Json::from(package)),
+                    ("train", Json::from(train)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::TrainMissingAttributes {
+                package,
+                train,
+                time,
+            } => (
+                "train_missing_attributes",
+                vec![
+                    ("package", Json::from(package)),
+                    ("train", Json::from(train)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::IncompatiblePackages {
+                package,
+                other_package,
+                train,
+                time,
+            } => (
+                "incompatible_packages",
+                vec![
+                    ("package", Json::from(package)),
+                    ("other_package", Json::from(other_package)),
+                    ("train", Json::from(train)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::PackageDeliveredToWrongStation {
+                package,
+                station,
+                time,
+            } => (
+                "package_delivered_to_wrong_station",
+                vec![
+                    ("package", Json::from(package)),
+                    ("station", Json::from(station)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::TransferNotAtHub {
+                package,
+                station,
+                time,
+            } => (
+                "transfer_not_at_hub",
+                vec![
+                    ("package", Json::from(package)),
+                    ("station", Json::from(station)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::TrainOverCapacity { train, load, time } => (
+                "train_over_capacity",
+                vec![
+                    ("train", Json::from(train)),
+                    ("load", Json::from(load)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::DimensionOverCapacity {
+                train,
+                dimension,
+                load,
+                time,
+            } => (
+                "dimension_over_capacity",
+                vec![
+                    ("train", Json::from(train)),
+                    ("dimension", Json::from(dimension)),
+                    ("load", Json::from(load)),
+                    ("time", Json::from(time)),
+                ],
+            ),
+            ValidationError::PackageNotDelivered { package } => (
+                "package_not_delivered",
+                vec![("package", Json::from(package))],
+            ),
+            ValidationError::TrainNotAtEnd { train, station } => (
+                "train_not_at_end",
+                vec![
+                    ("train", Json::from(train)),
+                    ("station", Json::from(station)),
+                ],
+            ),
+            ValidationError::RestBreakMissing { train, time } => (
+                "rest_break_missing",
+                vec![("train", Json::from(train)), ("time", Json::from(time))],
+            ),
+            ValidationError::OperatingTimeExceeded {
+                train,
+                operating_time,
+            } => (
+                "operating_time_exceeded",
+                vec![
+                    ("train", Json::from(train)),
+                    ("operating_time", Json::from(operating_time)),
+                ],
+            ),
+            ValidationError::DistanceExceeded { train, distance } => (
+                "distance_exceeded",
+                vec![
+                    ("train", Json::from(train)),
+                    ("distance", Json::from(distance)),
+                ],
+            ),
+        };
+        let mut entries: Vec<(String, Json)> = vec![("type".to_string(), Json::from(kind))];
+        for (key, value) in fields {
+            entries.push((key.to_string(), value));
+        }
+        Json::Object(entries)
+    }
+}
+
+impl Navigation {
+    pub fn validate_input(&self) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = vec![];
+
+        let mut edges: Vec<(String, (String, String, i32))> =
+            self.edges.clone().into_iter().collect();
+        edges.sort_by(|(edge_a, _), (edge_b, _)| edge_a.cmp(edge_b));
+        for (name, (from, to, distance)) in edges {
+            let from_coordinates = self
+                .graph
+                .get_station_id(&from)
+                .and_then(|id| self.graph.coordinates.get(&id));
+            let to_coordinates = self
+                .graph
+                .get_station_id(&to)
+                .and_then(|id| self.graph.coordinates.get(&id));
+            let straight_line_distance = match (from_coordinates, to_coordinates) {
+                (Some(from_coordinates), Some(to_coordinates)) => {
+                    from_coordinates.distance_to(to_coordinates)
+                }
+                _ => None,
+            };
+            if let Some(straight_line_distance) = straight_line_distance {
+                if (distance as f64) < straight_line_distance {
+                    errors.push(ValidationError::EdgeShorterThanStraightLine {
+                        edge: name,
+                        distance,
+                        straight_line_distance,
+                    });
+                }
+            }
+        }
+
+        let reachable_packages = self.get_reachable_packages();
+        let mut packages: Vec<(String, Package)> = self.packages.clone().into_iter().collect();
+        packages.sort_by(|(package_a, _), (package_b, _)| package_a.cmp(package_b));
+        for (name, package) in packages {
+            if !self.graph.is_connected(&package.from, &package.to) {
+                errors.push(ValidationError::PackageUnreachable {
+                    package: name.clone(),
+                });
+            } else if !reachable_packages
+                .iter()
+                .any(|(_, packages)| packages.contains(&name))
+            {
+                errors.push(ValidationError::NoTrainCanReachPackage {
+                    package: name.clone(),
+                });
+            }
+            if !self
+                .trains
+                .values()
+                .any(|train| train.capacity >= package.weight)
+            {
+                errors.push(ValidationError::PackageTooHeavy {
+                    package: name,
+                    weight: package.weight,
+                });
+            } else if !self.trains.values().any(|train| package.fits_in(train)) {
+                errors.push(ValidationError::PackageTooLarge { package: name });
+            } else if !self
+                .trains
+                .values()
+                .any(|train| package.fits_in(train) && package.is_allowed_on(&train.name))
+            {
+                errors.push(ValidationError::NoAllowedTrain { package: name });
+            } else if !self.trains.values().any(|train| {
+                package.fits_in(train)
+                    && package.is_allowed_on(&train.name)
+                    && package.is_supported_by(train)
+            }) {
+                errors.push(ValidationError::NoSupportingTrain { package: name });
+            }
+        }
+
+        errors
+    }
+
+    // Checks a plan, for example one made by hand or by another planner,
+    // against the network, trains and packages.
+    pub fn validate_plan(&self, movements: Vec<Movement>) -> Vec<ValidationError> {
+        let mut errors: Vec<ValidationError> = vec![];
+        let mut movements = movements;
+        movements.sort_by(|movement_a, movement_b| {
+            movement_a
+                .start_time
+                .cmp(&movement_b.start_time)
+                .then(movement_a.train.cmp(&movement_b.train))
+        });
+
+        let mut trains = self.trains.clone();
+        // Where each package is: a station, or the train it is on.
+        let mut package_stations: HashMap<String, String> = HashMap::new();
+        let mut package_trains: HashMap<String, String> = HashMap::new();
+        let mut delivered_packages: Vec<String> = vec![];
+        let mut moved_trains: Vec<String> = vec![];
+        for (name, package) in self.packages.iter() {
+            package_stations.insert(name.clone(), package.from.clone());
+        }
+
+        for movement in movements.clone() {
+            let time = movement.start_time;
+            let train = match trains.get_mut(&movement.train) {
+                Some(train) => train,
+                None => {
+                    errors.push(ValidationError::UnknownTrain {
+                        train: movement.train.clone(),
+                    });
+                    continue;
+                }
+            };
+
+            if train.current_location != movement.from {
+                errors.push(ValidationError::TrainNotAtStation {
+                    train: train.name.clone(),
+                    station: movement.from.clone(),
+                    time,
+                });
+            }
+            if !moved_trains.contains(&train.name) && time < train.available_at {
+                errors.push(ValidationError::TrainNotAvailable {
+                    train: train.name.clone(),
+                    time,
+                });
+            } else if time < train.available_at {
+                errors.push(ValidationError::MovementsOverlap {
+                    train: train.name.clone(),
+                    time,
+                });
+            }
+
+            let duration = movement.end_time - movement.start_time;
+            let (from_id, to_id) = (
+                self.graph.get_station_id(&movement.from),
+                self.graph.get_station_id(&movement.to),
+            );
+            // Movements that name their edge have to be on that one.
+            let is_on_edge = self.graph.edges.iter().any(|(from, to, distance, name)| {
+                *distance == duration
+                    && (movement.edge.is_empty() || *name == movement.edge)
+                    && ((Some(*from) == from_id && Some(*to) == to_id)
+                        || (Some(*from) == to_id && Some(*to) == from_id))
+            });
+            // A movement from a station to itself is a break.
+            if movement.from == movement.to {
+                if duration >= train.rest_duration {
+                    train.driven_since_rest = 0;
+                }
+            } else {
+                if train.needs_rest(duration) {
+                    errors.push(ValidationError::RestBreakMissing {
+                        train: train.name.clone(),
+                        time,
+                    });
+                }
+                train.driven_since_rest += duration;
+            }
+            match (from_id, to_id) {
+                _ if movement.from == movement.to => {}
+                (Some(from_id), Some(to_id)) if is_on_edge => {
+                    if self
+                        .graph
+                        .get_departure_time(from_id, to_id, duration, time)
+                        != time
+                    {
+                        errors.push(ValidationError::MovementDuringClosure {
+                            train: train.name.clone(),
+                            from: movement.from.clone(),
+                            to: movement.to.clone(),
+                            time,
+                        });
+                    }
+                }
+                _ => errors.push(ValidationError::MovementNotOnEdge {
+                    train: train.name.clone(),
+                    from: movement.from.clone(),
+                    to: movement.to.clone(),
+                    time,
+                }),
+            }
+
+            for package_name in movement.packages_picked_up.iter() {
+                let package = match self.packages.get(package_name) {
+                    Some(package) => package,
+                    None => {
+                        errors.push(ValidationError::UnknownPackage {
+                            package: package_name.clone(),
+                        });
+                        continue;
+                    }
+                };
+                if package_stations.get(package_name) != Some(&movement.from)
+                    || time < package.available_at
+                {
+                    errors.push(ValidationError::PackageNotAtStation {
+                        package: package_name.clone(),
+                        station: movement.from.clone(),
+                        time,
+                    });
+                }
+                if !package.is_allowed_on(&train.name) {
+                    errors.push(ValidationError::ForbiddenAssignment {
+                        package: package_name.clone(),
+                        train: train.name.clone(),
+                        time,
+                    });
+                }
+                if !package.is_supported_by(train) {
+                    errors.push(ValidationError::TrainMissingAttributes {
+                        package: package_name.clone(),
+                        train: train.name.clone(),
+                        time,
+                    });
+                }
+                for other_package_name in train.packages_picked_up.iter() {
+                    if let Some(other_package) = self.packages.get(other_package_name) {
+                        if !package.is_compatible_with(other_package) {
+                            errors.push(ValidationError::IncompatiblePackages {
+                                package: package_name.clone(),
+                                other_package: other_package_name.clone(),
+                                train: train.name.clone(),
+                                time,
+                            });
+                        }
+                    }
+                }
+                package_stations.remove(package_name);
+                package_trains.insert(package_name.clone(), train.name.clone());
+                train.packages_picked_up.push(package_name.clone());
+            }
+
+            let load: i32 = train
+                .packages_picked_up
+                .iter()
+                .filter_map(|package_name| self.packages.get(package_name))
+                .map(|package| package.weight)
+                .sum();
+            if load > train.capacity {
+                errors.push(ValidationError::TrainOverCapacity {
+                    train: train.name.clone(),
+                    load,
+                    time,
+                });
+            }
+            for (i, dimension) in self.dimensions.iter().enumerate() {
+                let load: i32 = train
+                    .packages_picked_up
+                    .iter()
+                    .filter_map(|package_name| self.packages.get(package_name))
+                    .filter_map(|package| package.sizes.get(i))
+                    .sum();
+                if load > train.capacities.get(i).copied().unwrap_or(i32::MAX) {
+                    errors.push(ValidationError::DimensionOverCapacity {
+                        train: train.name.clone(),
+                        dimension: dimension.clone(),
+                        load,
+                        time,
+                    });
+                }
+            }
+
+            for package_name in movement.packages_delivered.iter() {
+                let package = match self.packages.get(package_name) {
+                    Some(package) => package,
+                    None => {
+                        errors.push(ValidationError::UnknownPackage {
+                            package: package_name.clone(),
+                        });
+                        continue;
+                    }
+                };
+                if package_trains.get(package_name) != Some(&train.name) {
+                    errors.push(ValidationError::PackageNotOnTrain {
+                        package: package_name.clone(),
+                        train: train.name.clone(),
+                        time,
+                    });
+                }
+                if package.to != movement.to {
+                    errors.push(ValidationError::PackageDeliveredToWrongStation {
+                        package: package_name.clone(),
+                        station: movement.to.clone(),
+                        time,
+                    });
+                }
+                package_trains.remove(package_name);
+                package_stations.insert(package_name.clone(), movement.to.clone());
+                train
+                    .packages_picked_up
+                    .retain(|package_picked_up| package_picked_up != package_name);
+                delivered_packages.push(package_name.clone());
+            }
+
+            for package_name in movement.packages_transferred.iter() {
+                if !self.packages.contains_key(package_name) {
+                    errors.push(ValidationError::UnknownPackage {
+                        package: package_name.clone(),
+                    });
+                    continue;
+                }
+                if package_trains.get(package_name) != Some(&train.name) {
+                    errors.push(ValidationError::PackageNotOnTrain {
+                        package: package_name.clone(),
+                        train: train.name.clone(),
+                        time,
+                    });
+                }
+                if !self.hubs.contains(&movement.to) {
+                    errors.push(ValidationError::TransferNotAtHub {
+                        package: package_name.clone(),
+                        station: movement.to.clone(),
+                        time,
+                    });
+                }
+                package_trains.remove(package_name);
+                package_stations.insert(package_name.clone(), movement.to.clone());
+                train
+                    .packages_picked_up
+                    .retain(|package_picked_up| package_picked_up != package_name);
+            }
+
+            train.current_location = movement.to.clone();
+            train.available_at = movement.end_time;
+            if !moved_trains.contains(&train.name) {
+                moved_trains.push(train.name.clone());
+            }
+        }
+
+        let mut package_names: Vec<String> = self.packages.keys().cloned().collect();
+        package_names.sort();
+        for package_name in package_names {
+            if !delivered_packages.contains(&package_name) {
+                errors.push(ValidationError::PackageNotDelivered {
+                    package: package_name,
+                });
+            }
+        }
+        moved_trains.sort();
+        for train_name in moved_trains {
+            let train = &trains[&train_name];
+            if !train.end.is_empty() && train.current_location != train.end {
+                errors.push(ValidationError::TrainNotAtEnd {
+                    train: train_name.clone(),
+                    station: train.end.clone(),
+                });
+            }
+            let (operating_time, distance) = Navigation::get_train_usage(&train_name, &movements);
+            if train
+                .max_operating_time
+                .is_some_and(|max_operating_time| operating_time > max_operating_time)
+            {
+                errors.push(ValidationError::OperatingTimeExceeded {
+                    train: train_name.clone(),
+                    operating_time,
+                });
+            }
+            if train
+                .max_distance
+                .is_some_and(|max_distance| distance > max_distance)
+            {
+                errors.push(ValidationError::DistanceExceeded {
+                    train: train_name.clone(),
+                    distance,
+                });
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Coordinates, input::Input, movement::Movement, navigation::Navigation};
+
+    use super::ValidationError;
+
+    #[test]
+    fn test_validate_infeasible_packages() {
+        let navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 10),
+                ("E2".to_string(), "X".to_string(), "Y".to_string(), 10),
+                ("E3".to_string(), "P".to_string(), "R".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "X".to_string()),
+                ("K2".to_string(), 5, "X".to_string(), "Y".to_string()),
+                ("K3".to_string(), 50, "A".to_string(), "B".to_string()),
+                ("K4".to_string(), 5, "A".to_string(), "B".to_string()),
+            ],
+            trains: vec![("Q1".to_string(), 10, "B".to_string())],
+            ..Default::default()
+        });
+        assert_eq!(
+            navigation.validate_input(),
+            vec![
+                ValidationError::PackageUnreachable {
+                    package: "K1".to_string(),
+                },
+                ValidationError::NoTrainCanReachPackage {
+                    package: "K2".to_string(),
+                },
+                ValidationError::PackageTooHeavy {
+                    package: "K3".to_string(),
+                    weight: 50,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_edge_lengths() {
+        let navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![("K1".to_string(), 5, "A".to_string(), "C".to_string())],
+            trains: vec![("Q1".to_string(), 6, "B".to_string())],
+            stations: vec![
+                ("A".to_string(), Coordinates::Planar { x: 0.0, y: 0.0 }),
+                ("B".to_string(), Coordinates::Planar { x: 30.0, y: 0.0 }),
+                ("C".to_string(), Coordinates::Planar { x: 30.0, y: 20.0 }),
+            ],
+            ..Default::default()
+        });
+        assert_eq!(
+            navigation.validate_input(),
+            vec![ValidationError::EdgeShorterThanStraightLine {
+                edge: "E2".to_string(),
+                distance: 10,
+                straight_line_distance: 20.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_plan() {
+        let mut navigation = Navigation::new(Input {
+            edges: vec![
+                ("E1".to_string(), "A".to_string(), "B".to_string(), 30),
+                ("E2".to_string(), "B".to_string(), "C".to_string(), 10),
+            ],
+            packages: vec![
+                ("K1".to_string(), 5, "A".to_string(), "C".to_string()),
+                ("K2".to_string(), 5, "B".to_string(), "C".to_string()),
+            ],
+            trains: vec![("Q1".to_string(), 6, "A".to_string())],
+            closures: vec![("E2".to_string(), 50, 60)],
+            ..Default::default()
+        });
+        let movements = navigation.calculate(
+            navigation.trains.clone(),
+            navigation.packages.clone(),
+            vec![],
+        );
+        assert_eq!(navigation.validate_plan(movements), vec![]);
+
+        let movement = |start_time: i32, end_time: i32, from: &str, to: &str| Movement {
+            start_time,
+            end_time,
+            from: from.to_string(),
+            to: to.to_string(),
+            edge: "".to_string(),
+            train: "Q1".to_string(),
+            packages_picked_up: vec![],
+            packages_delivered: vec![],
+            packages_transferred: vec![],
+        };
+        assert_eq!(
+            navigation.validate_plan(vec![
+                Movement {
+                    packages_picked_up: vec!["K1".to_string(), "K2".to_string()],
+                    ..movement(0, 20, "A", "B")
+                },
+                Movement {
+                    packages_delivered: vec!["K1".to_string(), "K2".to_string()],
+                    packages_transferred: vec![],
+                    ..movement(45, 55, "B", "C")
+                },
+            ]),
+            vec![
+                ValidationError::MovementNotOnEdge {
+                    train: "Q1".to_string(),
+                    from: "A".to_string(),
+                    to: "B".to_string(),
+                    time: 0,
+                },
+                ValidationError::PackageNotAtStation {
+                    package: "K2".to_string(),
+                    station: "A".to_string(),
+                    time: 0,
+                },
+                ValidationError::TrainOverCapacity {
+                    train: "Q1".to_string(),
+                    load: 10,
+                    time: 0,
+                },
+                ValidationError::MovementDuringClosure {
+                    train: "Q1".to_string(),
+                    from: "B".to_string(),
+                    to: "C".to_string(),
+                    time: 45,
+                },
+                ValidationError::TrainOverCapacity {
+                    train: "Q1".to_string(),
+                    load: 10,
+                    time: 45,
+                },
+            ]
+        );
+    }
+}